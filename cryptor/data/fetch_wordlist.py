@@ -0,0 +1,19 @@
+# Downloads the EFF large wordlist (https://www.eff.org/dice) next to this script, unmodified:
+#   python3 fetch_wordlist.py
+# It is only written if it has the published shape: 7776 "<five dice><TAB><word>" lines, 11111 to 66666.
+import itertools
+import os
+import urllib.request
+
+URL = "https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt"
+
+with urllib.request.urlopen(URL) as response:
+    data = response.read()
+
+rolls = [line.split(b"\t")[0].decode() for line in data.splitlines()]
+expected = ["".join(roll) for roll in itertools.product("123456", repeat=5)]
+if rolls != expected:
+    raise SystemExit(f"unexpected content from {URL} ({len(rolls)} lines)")
+
+with open(os.path.join(os.path.dirname(os.path.abspath(__file__)), "eff_large_wordlist.txt"), "wb") as out:
+    out.write(data)
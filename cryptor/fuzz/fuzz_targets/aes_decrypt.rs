@@ -0,0 +1,19 @@
+#![no_main]
+
+use cryptor::crypto::aes;
+use libfuzzer_sys::fuzz_target;
+
+const KEY: [u8; aes::KEY_LEN] = [0x42; aes::KEY_LEN];
+
+// Any input is rejected or decrypted, never a panic. The password layer runs with a single
+// PBKDF2 iteration (the seeds are sealed that way) so that executions are not spent in the
+// key derivation; the keyed layer takes the first 12 bytes as the nonce.
+fuzz_target!(|data: &[u8]| {
+    let _ = aes::decrypt_with_aad("fuzz", 1, &[], data);
+
+    if let Some((nonce, sealed)) = data.split_at_checked(aes::NONCE_LEN) {
+        let _ = aes::open(&KEY, nonce, &[], sealed);
+        let resealed = aes::seal(&KEY, nonce, &[], sealed).unwrap();
+        assert_eq!(aes::open(&KEY, nonce, &[], &resealed).unwrap(), sealed);
+    }
+});
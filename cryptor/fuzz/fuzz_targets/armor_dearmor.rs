@@ -0,0 +1,14 @@
+#![no_main]
+
+use cryptor::crypto::armor;
+use libfuzzer_sys::fuzz_target;
+
+// Armor parser (headers, Base64 body, CRC-24 line, footer): no panic, and armor then
+// dearmor gives back any bytes
+fuzz_target!(|data: &[u8]| {
+    let _ = armor::dearmor(data);
+
+    let armored = armor::armor(armor::PGP_MESSAGE, data).unwrap();
+    assert!(armor::is_armored(armored.as_bytes()));
+    assert_eq!(armor::dearmor(armored.as_bytes()).unwrap(), data);
+});
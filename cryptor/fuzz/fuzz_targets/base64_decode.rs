@@ -0,0 +1,18 @@
+#![no_main]
+
+use cryptor::crypto::base64;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Any bytes: encode then decode is the identity
+    assert_eq!(base64::decode(&base64::encode(data)).unwrap(), data);
+    assert_eq!(base64::decode_url(&base64::encode_url(data)).unwrap(), data);
+    assert_eq!(base64::decode_url_padded(&base64::encode_url_padded(data)).unwrap(), data);
+
+    // Any text: rejected, or decoded to bytes that survive another round trip
+    if let Ok(text) = std::str::from_utf8(data)
+        && let Ok(decoded) = base64::decode(text)
+    {
+        assert_eq!(base64::decode(&base64::encode(&decoded)).unwrap(), decoded);
+    }
+});
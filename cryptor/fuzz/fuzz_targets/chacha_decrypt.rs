@@ -0,0 +1,19 @@
+#![no_main]
+
+use cryptor::crypto::chacha;
+use libfuzzer_sys::fuzz_target;
+
+const KEY: [u8; chacha::KEY_LEN] = [0x42; chacha::KEY_LEN];
+
+// Any input is rejected or decrypted, never a panic. The password layer runs with a single
+// PBKDF2 iteration (the seeds are sealed that way) so that executions are not spent in the
+// key derivation; the keyed layer takes the first 12 bytes as the nonce.
+fuzz_target!(|data: &[u8]| {
+    let _ = chacha::decrypt_with_aad("fuzz", 1, &[], data);
+
+    if let Some((nonce, sealed)) = data.split_at_checked(chacha::NONCE_LEN) {
+        let _ = chacha::open(&KEY, nonce, &[], sealed);
+        let resealed = chacha::seal(&KEY, nonce, &[], sealed).unwrap();
+        assert_eq!(chacha::open(&KEY, nonce, &[], &resealed).unwrap(), sealed);
+    }
+});
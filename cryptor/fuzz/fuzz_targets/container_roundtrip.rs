@@ -0,0 +1,42 @@
+#![no_main]
+
+use cryptor::crypto::compress::{self, Codec};
+use cryptor::crypto::container::{self, Header};
+use cryptor::crypto::metadata::Metadata;
+use cryptor::crypto::padding::Padding;
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the algorithm and options, the rest is the plaintext.
+// compress, seal, open, decompress must give back the plaintext and the same header.
+fuzz_target!(|data: &[u8]| {
+    let Some((&options, plain)) = data.split_first() else {
+        return;
+    };
+
+    let header = Header {
+        compression: [Codec::None, Codec::Gzip, Codec::Zstd][options as usize % 3],
+        padding: match (options >> 2) % 3 {
+            0 => Padding::None,
+            1 => Padding::Padme,
+            _ => Padding::Block(1 + (options >> 4) as u32 * 61),
+        },
+        metadata: (options & 0x80 != 0).then(|| Metadata {
+            name: Some("fuzz.bin".to_string()),
+            mode: Some(0o640),
+            mtime: Some((-1, 999_999_999)),
+            xattrs: vec![("user.fuzz".to_string(), plain.to_vec())],
+        }),
+        // One iteration: the default would spend every execution in PBKDF2
+        kdf_iterations: Some(1),
+    };
+    let algo = if options & 0x40 != 0 { "chacha" } else { "aes" };
+
+    let compressed = compress::compress(header.compression, plain).unwrap();
+    let sealed = container::seal(algo, "fuzz", &header, &compressed).unwrap();
+    let (opened_header, opened) = container::open(algo, "fuzz", &sealed).unwrap();
+    assert_eq!(opened_header, header);
+
+    let mut output = Vec::new();
+    compress::decompress(header.compression, &opened, &mut output).unwrap();
+    assert_eq!(output, plain);
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use cryptor::crypto::encoding;
+use libfuzzer_sys::fuzz_target;
+
+// Ascii85, Z85, base32, base32hex, base58...: any text is rejected or decoded to bytes that
+// survive another round trip, and any bytes encode then decode to themselves
+fuzz_target!(|data: &[u8]| {
+    for algo in encoding::ALGOS {
+        if let Ok(encoded) = encoding::encode(algo, data) {
+            assert_eq!(encoding::decode(algo, &encoded).unwrap(), data, "{}", algo);
+        }
+
+        if let Ok(text) = std::str::from_utf8(data)
+            && let Ok(decoded) = encoding::decode(algo, text)
+            && let Ok(encoded) = encoding::encode(algo, &decoded)
+        {
+            assert_eq!(encoding::decode(algo, &encoded).unwrap(), decoded, "{}", algo);
+        }
+    }
+});
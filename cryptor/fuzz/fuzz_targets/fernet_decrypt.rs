@@ -0,0 +1,21 @@
+#![no_main]
+
+use cryptor::crypto::fernet;
+use cryptor::crypto::secret::SecretString;
+use libfuzzer_sys::fuzz_target;
+
+// Fernet token parsing (Base64, version, timestamp, HMAC, CBC padding) with a fixed key;
+// the second key checks the rotation path
+fuzz_target!(|data: &[u8]| {
+    let keys: Vec<SecretString> = [
+        "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+        "QkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkI=",
+    ]
+    .iter()
+    .map(|key| key.parse().unwrap())
+    .collect();
+
+    for ttl in [None, Some(60)] {
+        let _ = fernet::decrypt(&keys, data, ttl);
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use cryptor::crypto::container::Header;
+use cryptor::crypto::metadata::Metadata;
+use libfuzzer_sys::fuzz_target;
+
+// Parsers for the native file header and the metadata block: no panic, lengths stay in bounds
+fuzz_target!(|data: &[u8]| {
+    if let Ok(Some((_, len))) = Header::parse(data) {
+        assert!(len <= data.len());
+    }
+
+    if let Ok((metadata, used)) = Metadata::decode(data) {
+        assert!(used <= data.len());
+        assert_eq!(Metadata::decode(&metadata.encode()).unwrap().0, metadata);
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use cryptor::crypto::inspect;
+use libfuzzer_sys::fuzz_target;
+
+// Every parser behind `inspect` (native header, armor, OpenPGP packets, ZIP, Ansible Vault,
+// age, JWE, Fernet) sees untrusted input: it must report, never panic.
+fuzz_target!(|data: &[u8]| {
+    let _ = inspect::inspect(data).to_json();
+});
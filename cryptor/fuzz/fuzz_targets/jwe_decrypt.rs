@@ -0,0 +1,12 @@
+#![no_main]
+
+use cryptor::crypto::jwe::{self, KeyManagement};
+use libfuzzer_sys::fuzz_target;
+
+// Compact JWE: five segments, protected header JSON, alg / enc checks and A256GCM.
+// "dir" keeps it keyed; PBES2 only derives a key when the header is well formed, and its p2c
+// comes from the input (capped by jwe::decrypt).
+fuzz_target!(|data: &[u8]| {
+    let _ = jwe::decrypt(KeyManagement::Dir, "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8", data);
+    let _ = jwe::decrypt(KeyManagement::Pbes2, "fuzz", data);
+});
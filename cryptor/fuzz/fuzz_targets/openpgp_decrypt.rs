@@ -0,0 +1,11 @@
+#![no_main]
+
+use cryptor::crypto::openpgp;
+use libfuzzer_sys::fuzz_target;
+
+// OpenPGP packet framing, then SKESK / SEIPD / compressed and literal packets.
+// The S2K count is read from the input: inputs asking for the largest counts run slowly.
+fuzz_target!(|data: &[u8]| {
+    let _ = openpgp::read_packets(data);
+    let _ = openpgp::decrypt("fuzz", data);
+});
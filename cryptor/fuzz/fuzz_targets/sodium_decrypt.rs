@@ -0,0 +1,17 @@
+#![no_main]
+
+use cryptor::crypto::sodium;
+use cryptor::crypto::verify::Verdict;
+use libfuzzer_sys::fuzz_target;
+
+const KEY: [u8; sodium::KEY_LEN] = [0x42; sodium::KEY_LEN];
+
+// secretbox (nonce || box) and secretstream (header || chunks) with a fixed key: no panic,
+// and verify agrees with decrypt
+fuzz_target!(|data: &[u8]| {
+    let _ = sodium::secretbox_decrypt(&KEY, data);
+
+    let decrypted = sodium::secretstream_decrypt(&KEY, data);
+    let valid = matches!(sodium::secretstream_verify(&KEY, data), Verdict::Valid { .. });
+    assert_eq!(decrypted.is_ok(), valid);
+});
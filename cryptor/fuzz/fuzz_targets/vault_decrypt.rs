@@ -0,0 +1,12 @@
+#![no_main]
+
+use cryptor::crypto::vault;
+use libfuzzer_sys::fuzz_target;
+
+// $ANSIBLE_VAULT envelope, hex layers, HMAC and padding. The key derivation (10 000 PBKDF2
+// iterations) only runs once the envelope parses.
+fuzz_target!(|data: &[u8]| {
+    let _ = vault::parse_envelope(data);
+    let _ = vault::vault_id(data);
+    let _ = vault::decrypt("fuzz", data);
+});
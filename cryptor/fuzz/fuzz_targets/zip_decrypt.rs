@@ -0,0 +1,16 @@
+#![no_main]
+
+use cryptor::crypto::zip;
+use libfuzzer_sys::fuzz_target;
+
+// End of central directory, central and local headers, AES extra fields and entry names:
+// listed and decrypted without a panic, and nothing extracted outside the output directory
+fuzz_target!(|data: &[u8]| {
+    let _ = zip::list(data);
+
+    if let Ok(entries) = zip::decrypt("fuzz", data) {
+        for entry in entries {
+            assert!(!entry.name.starts_with('/') && !entry.name.split('/').any(|part| part == ".."));
+        }
+    }
+});
@@ -1,6 +1,6 @@
 use clap::{Parser, Subcommand};
 use crate::io;
-use crate::crypto::{aes, chacha, xor, base64};
+use crate::crypto::{container, compress, padding, secret::{SecretBytes, SecretString}, sodium, openssl, openpgp, fernet, jwe, vault, zip, xor, armor, base64, encoding, mac, inspect, verify::{self, Verdict}, selftest, bench, random};
 use anyhow::Result;
 
 #[derive(Parser)]
@@ -10,9 +10,95 @@ pub struct Args {
     command: Commands,
 }
 
+// secretstream chunk size (sodium::CHUNK_LEN), shown by --help
+const SECRETSTREAM_HELP: &str = "secretstream cuts the plaintext into 4096-byte chunks: a stream written by another \
+libsodium program only decrypts if it used 4096-byte chunks too (the last one may be shorter)";
+
 #[derive(Subcommand)]
 pub enum Commands {
+    #[command(after_help = SECRETSTREAM_HELP)]
     Encrypt {
+        #[arg(long)]
+        algo: String,
+        #[arg(long, required_unless_present = "text", conflicts_with = "text")]
+        input: Option<String>,
+        #[arg(long, required_unless_present_any = ["text", "in_place"], conflicts_with = "text")]
+        output: Option<String>,
+        #[arg(long)]
+        password: Option<SecretString>,
+        // Key derivation for aes-256-cbc / aes-256-ctr: pbkdf2, sha256 or md5
+        #[arg(long, default_value = "pbkdf2")]
+        openssl_kdf: String,
+        // Fernet key (the first one is used to encrypt), JWE "dir" key or libsodium key (hex)
+        #[arg(long)]
+        key: Vec<SecretString>,
+        // JWE key management with --password: PBES2-HS256+A128KW or PBES2-HS512+A256KW
+        #[arg(long, default_value = "PBES2-HS256+A128KW")]
+        jwe_alg: String,
+        // Ansible Vault id (writes a 1.2 header)
+        #[arg(long)]
+        vault_id: Option<String>,
+        #[arg(long)]
+        armor: bool,
+        // Compression before encryption for aes / chacha: none, gzip or zstd
+        #[arg(long)]
+        compress: Option<String>,
+        // Length-hiding padding for aes / chacha: none, padme or block:N
+        #[arg(long)]
+        pad: Option<String>,
+        // Keep the file name, mode, mtime and extended attributes inside the ciphertext (aes / chacha)
+        #[arg(long, conflicts_with = "text")]
+        store_metadata: bool,
+        // Tune the PBKDF2 iterations to take about this long on this host (aes / chacha, recorded in the header;
+        // kept between 100 000 and 1 000 000)
+        #[arg(long)]
+        kdf_target_ms: Option<u64>,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
+        // Replace the input with its encrypted form
+        #[arg(long, conflicts_with_all = ["output", "text"])]
+        in_place: bool,
+        // Overwrite the plaintext input with random data, truncate and delete it
+        #[arg(long, conflicts_with = "text")]
+        shred: bool,
+        // Text to encrypt ("-" reads stdin); the token is printed
+        #[arg(long)]
+        text: Option<String>,
+    },
+    #[command(after_help = SECRETSTREAM_HELP)]
+    Decrypt {
+        #[arg(long)]
+        algo: String,
+        #[arg(long, required_unless_present = "text", conflicts_with = "text")]
+        input: Option<String>,
+        #[arg(long, required_unless_present_any = ["text", "output_dir"], conflicts_with = "text")]
+        output: Option<String>,
+        // Write into this directory, under the file name stored with --store-metadata
+        #[arg(long, conflicts_with_all = ["output", "text"])]
+        output_dir: Option<String>,
+        // Restore the mode, mtime and extended attributes stored with --store-metadata
+        #[arg(long, conflicts_with = "text")]
+        restore_metadata: bool,
+        #[arg(long)]
+        password: Option<SecretString>,
+        // Key derivation for aes-256-cbc / aes-256-ctr: pbkdf2, sha256 or md5
+        #[arg(long, default_value = "pbkdf2")]
+        openssl_kdf: String,
+        // Fernet keys, tried in order (MultiFernet), JWE "dir" key or libsodium key (hex)
+        #[arg(long)]
+        key: Vec<SecretString>,
+        // Maximum Fernet token age, in seconds
+        #[arg(long)]
+        ttl: Option<u64>,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
+        // Token to decrypt ("-" reads stdin); the plaintext is printed
+        #[arg(long)]
+        text: Option<String>,
+    },
+    Encode {
         #[arg(long)]
         algo: String,
         #[arg(long)]
@@ -20,9 +106,12 @@ pub enum Commands {
         #[arg(long)]
         output: String,
         #[arg(long)]
-        password: Option<String>,
+        wrap: Option<usize>,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
     },
-    Decrypt {
+    Decode {
         #[arg(long)]
         algo: String,
         #[arg(long)]
@@ -30,23 +119,117 @@ pub enum Commands {
         #[arg(long)]
         output: String,
         #[arg(long)]
-        password: Option<String>,
+        ignore_garbage: bool,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
     },
-    Encode {
+    Mac {
         #[arg(long)]
         algo: String,
         #[arg(long)]
         input: String,
         #[arg(long)]
         output: String,
+        #[arg(long)]
+        password: SecretString,
+        #[arg(long)]
+        append: bool,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
     },
-    Decode {
+    Keygen {
+        #[arg(long)]
+        algo: String,
+    },
+    Rotate {
         #[arg(long)]
         algo: String,
         #[arg(long)]
         input: String,
         #[arg(long)]
         output: String,
+        // Fernet: new key first, then the keys the token may currently be under
+        #[arg(long)]
+        key: Vec<SecretString>,
+        // Ansible Vault: current and new password (rekey)
+        #[arg(long)]
+        password: Option<SecretString>,
+        #[arg(long)]
+        new_password: Option<SecretString>,
+        // Replace the output if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+    View {
+        #[arg(long)]
+        algo: String,
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        password: SecretString,
+    },
+    MacVerify {
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        password: SecretString,
+    },
+    // Describe an encrypted file (no password needed)
+    Inspect {
+        input: String,
+        #[arg(long)]
+        json: bool,
+    },
+    // Authenticate an encrypted file without writing the plaintext anywhere
+    #[command(after_help = SECRETSTREAM_HELP)]
+    Verify {
+        // aes, chacha or secretstream. aes / chacha files are read whole and only get a
+        // whole-file verdict; secretstream is checked chunk by chunk
+        #[arg(long)]
+        algo: String,
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        password: Option<SecretString>,
+        // secretstream key (hex)
+        #[arg(long)]
+        key: Option<SecretString>,
+    },
+    // Run the built-in known-answer tests
+    SelfTest,
+    // Measure cipher throughput and key derivation cost on this host
+    Bench {
+        // Buffer encrypted by each cipher, in MiB
+        #[arg(long, default_value_t = 64)]
+        size: usize,
+        // Also show the PBKDF2 iterations encrypt --kdf-target-ms would pick
+        #[arg(long)]
+        kdf_target_ms: Option<u64>,
+    },
+    // Random bytes, passwords or diceware passphrases (OS random source)
+    Random {
+        // bytes, password or passphrase
+        #[arg(default_value = "bytes")]
+        kind: String,
+        // Number of bytes (default 32), characters (default 20) or words (default 6)
+        #[arg(long)]
+        length: Option<usize>,
+        // bytes: raw, hex or base64
+        #[arg(long, default_value = "hex")]
+        format: String,
+        // password: character sets, comma-separated (lower, upper, digits, symbols)
+        #[arg(long, default_value = "lower,upper,digits")]
+        charset: String,
+        // password: extra characters to draw from
+        #[arg(long)]
+        chars: Option<String>,
+        // passphrase: between words
+        #[arg(long, default_value = "-")]
+        separator: String,
     },
 }
 
@@ -60,95 +243,358 @@ pub fn run(args: Args) -> Result<()> {
         // ----------------------------------------------------------------------
         // ENCRYPT
         // ----------------------------------------------------------------------
-        Commands::Encrypt { algo, input, output, password } => {
+        Commands::Encrypt {
+            algo, input, output, password, openssl_kdf, key, jwe_alg, vault_id, armor, compress, pad,
+            store_metadata, kdf_target_ms, force, in_place, shred, text,
+        } => {
 
-            if !std::path::Path::new(&input).exists() {
+            if let Some(input) = &input
+                && !std::path::Path::new(input).exists()
+            {
                 anyhow::bail!("Input file does not exist: {}", input);
             }
 
-            let allowed = ["aes", "chacha", "xor"];
+            let allowed = [
+                "aes", "chacha", "xor", "aes-256-cbc", "aes-256-ctr", "gpg", "fernet", "jwe", "ansible-vault",
+                "secretbox", "secretstream", "zip",
+            ];
             if !allowed.contains(&algo.as_str()) {
-                anyhow::bail!("Invalid algorithm '{}'. Allowed: aes, chacha, xor", algo);
+                anyhow::bail!("Invalid algorithm '{}'. Allowed: {}", algo, allowed.join(", "));
+            }
+
+            if algo == "zip" && text.is_some() {
+                anyhow::bail!("--text is not supported with zip (use --input and --output)");
+            }
+
+            // Password / key requirements
+            match algo.as_str() {
+                "xor" => {
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with XOR");
+                    }
+                }
+                "fernet" => {
+                    if key.is_empty() {
+                        anyhow::bail!("--key is required for fernet");
+                    }
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with fernet (use --key)");
+                    }
+                }
+                "secretbox" | "secretstream" => {
+                    if key.len() != 1 {
+                        anyhow::bail!("{} needs exactly one --key (32 bytes as hex)", algo);
+                    }
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with {} (use --key)", algo);
+                    }
+                }
+                "jwe" => {
+                    if password.is_some() != key.is_empty() {
+                        anyhow::bail!("jwe needs either --password (PBES2) or --key (dir)");
+                    }
+                }
+                _ => {
+                    if password.is_none() {
+                        anyhow::bail!("--password is required for {}", algo);
+                    }
+                }
             }
 
-            // Password requirements
-            if algo == "aes" || algo == "chacha" {
-                if password.is_none() {
-                    anyhow::bail!("--password is required for AES and ChaCha20");
+            let codec = match &compress {
+                Some(_) if algo != "aes" && algo != "chacha" => {
+                    anyhow::bail!("--compress is only supported with aes and chacha");
                 }
+                Some(name) => Some(compress::Codec::parse(name)?),
+                None => None,
+            };
+            let padding = match &pad {
+                Some(_) if algo != "aes" && algo != "chacha" => {
+                    anyhow::bail!("--pad is only supported with aes and chacha");
+                }
+                Some(name) => padding::Padding::parse(name)?,
+                None => padding::Padding::None,
+            };
+            if store_metadata && algo != "aes" && algo != "chacha" {
+                anyhow::bail!("--store-metadata is only supported with aes and chacha");
             }
-            if algo == "xor" && password.is_some() {
-                anyhow::bail!("--password must NOT be used with XOR");
+            let kdf_iterations = match kdf_target_ms {
+                Some(_) if algo != "aes" && algo != "chacha" => {
+                    anyhow::bail!("--kdf-target-ms is only supported with aes and chacha");
+                }
+                Some(0) => anyhow::bail!("--kdf-target-ms must be greater than 0"),
+                Some(ms) => {
+                    let iterations = bench::calibrate(std::time::Duration::from_millis(ms));
+                    println!("[OK] PBKDF2: {} iterations (about {} ms on this host)", iterations, ms);
+                    Some(iterations)
+                }
+                None => None,
+            };
+
+            // ZIP: the input is a file or a whole directory
+            if algo == "zip" {
+                if armor {
+                    anyhow::bail!("--armor is not supported with zip");
+                }
+                if in_place || shred {
+                    anyhow::bail!("--in-place and --shred are not supported with zip");
+                }
+
+                let mut entries = Vec::new();
+                for (name, path) in io::walk(input.as_deref().unwrap())? {
+                    let data = if name.ends_with('/') { Vec::new() } else { io::read_file(&path)? };
+                    let data = SecretBytes::from(data);
+                    entries.push(zip::Entry { name, data, modified: io::modified(&path)? });
+                }
+
+                let output = output.unwrap();
+                io::write_file(&output, &zip::encrypt(&password.unwrap(), &entries)?, force)?;
+
+                println!("[OK] Encrypted {} entries using zip → {}", entries.len(), output);
+                return Ok(());
             }
 
-            let data = io::read_file(&input)?;
+            // Plaintext: wiped from memory once encrypted
+            let data = SecretBytes::from(match (&text, &input, codec) {
+                (Some(t), _, _) if t == "-" => io::read_stdin()?,
+                (Some(t), _, _) => t.as_bytes().to_vec(),
+                // Compressed while reading: the uncompressed file is never held in memory
+                (None, Some(input), Some(codec)) => compress::compress(codec, io::open_reader(input)?)?,
+                (None, Some(input), None) => io::read_file(input)?,
+                (None, None, _) => unreachable!(),
+            });
+            let data = match (&text, codec) {
+                (Some(_), Some(codec)) => SecretBytes::from(compress::compress(codec, data.as_slice())?),
+                _ => data,
+            };
 
-            match algo.as_str() {
-                "aes" => {
-                    let pwd = password.unwrap();
-                    let encrypted = aes::encrypt(&pwd, &data)?;
-                    io::write_file(&output, &encrypted)?;
+            let encrypted = match algo.as_str() {
+                "aes" | "chacha" => {
+                    let pwd = password.as_deref().unwrap();
+                    let metadata = match &input {
+                        Some(input) if store_metadata => Some(io::read_metadata(input)?),
+                        _ => None,
+                    };
+                    let header = container::Header {
+                        compression: codec.unwrap_or_default(),
+                        padding,
+                        metadata,
+                        kdf_iterations,
+                    };
+                    container::seal(&algo, pwd, &header, &data)?
                 }
-                "chacha" => {
-                    let pwd = password.unwrap();
-                    let encrypted = chacha::encrypt(&pwd, &data)?;
-                    io::write_file(&output, &encrypted)?;
+                "xor" => xor::xor_encrypt(xor::KEY, &data),
+                "aes-256-cbc" | "aes-256-ctr" => {
+                    let pwd = password.as_deref().unwrap();
+                    openssl::encrypt(&algo, &openssl_kdf, pwd, &data)?
                 }
-                "xor" => {
-                    let key = b"supersecretkey";
-                    let encrypted = xor::xor_encrypt(key, &data);
-                    io::write_file(&output, &encrypted)?;
+                "gpg" => {
+                    let pwd = password.as_deref().unwrap();
+                    openpgp::encrypt(pwd, &data)?
+                }
+                "secretbox" => sodium::secretbox_encrypt(&*sodium::parse_key(&key[0])?, &data)?,
+                "secretstream" => sodium::secretstream_encrypt(&*sodium::parse_key(&key[0])?, &data)?,
+                "fernet" => fernet::encrypt(&key[0], &data)?,
+                "jwe" => match &password {
+                    Some(pwd) => jwe::encrypt(&jwe_alg, pwd, &data)?,
+                    None => jwe::encrypt("dir", &key[0], &data)?,
+                },
+                "ansible-vault" => {
+                    let pwd = password.as_deref().unwrap();
+                    vault::encrypt(pwd, vault_id.as_deref(), &data)?
                 }
                 _ => unreachable!(),
+            };
+
+            let armor_label = if algo == "gpg" { armor::PGP_MESSAGE } else { armor::CRYPTOR_MESSAGE };
+
+            // Inline text: print a token instead of writing a file
+            if text.is_some() {
+                if armor {
+                    print!("{}", armor::armor(armor_label, &encrypted)?);
+                } else if algo == "fernet" || algo == "jwe" || algo == "ansible-vault" {
+                    println!("{}", String::from_utf8_lossy(&encrypted));
+                } else {
+                    println!("{}", base64::encode(&encrypted));
+                }
+                return Ok(());
+            }
+
+            // --in-place / --shred destroy the plaintext: first make sure the ciphertext decrypts back to it
+            if in_place || shred {
+                let (_, roundtrip) =
+                    decrypt_data(&algo, password.as_deref(), &key, &openssl_kdf, None, &encrypted)?;
+                if roundtrip != data {
+                    anyhow::bail!("Verification failed: the ciphertext does not decrypt to the input (nothing was written)");
+                }
+            }
+
+            let input = input.unwrap();
+            let output = if in_place { input.clone() } else { output.unwrap() };
+            let encrypted = if armor { armor::armor(armor_label, &encrypted)?.into_bytes() } else { encrypted };
+
+            if in_place || shred {
+                // Opened before the rename, so the old plaintext can still be overwritten afterwards
+                let original = if in_place && shred { Some(io::open_for_shred(&input)?) } else { None };
+
+                io::write_file_verified(&output, &encrypted, force || in_place)?;
+
+                match original {
+                    Some(file) => io::shred_file(&file)?,
+                    None if shred => io::shred(&input)?,
+                    None => {}
+                }
+            } else {
+                io::write_file(&output, &encrypted, force)?;
             }
 
             println!("[OK] Encrypted using {} → {}", algo, output);
+            if shred {
+                println!("[OK] Plaintext shredded: {}", input);
+            }
             Ok(())
         }
 
         // ----------------------------------------------------------------------
         // DECRYPT
         // ----------------------------------------------------------------------
-        Commands::Decrypt { algo, input, output, password } => {
+        Commands::Decrypt {
+            algo, input, output, output_dir, restore_metadata, password, openssl_kdf, key, ttl, force, text,
+        } => {
 
-            if !std::path::Path::new(&input).exists() {
+            if let Some(input) = &input
+                && !std::path::Path::new(input).exists()
+            {
                 anyhow::bail!("Input file does not exist: {}", input);
             }
 
-            let allowed = ["aes", "chacha", "xor"];
+            let allowed = [
+                "aes", "chacha", "xor", "aes-256-cbc", "aes-256-ctr", "gpg", "fernet", "jwe", "ansible-vault",
+                "secretbox", "secretstream", "zip",
+            ];
             if !allowed.contains(&algo.as_str()) {
-                anyhow::bail!("Invalid algorithm '{}'. Allowed: aes, chacha, xor", algo);
+                anyhow::bail!("Invalid algorithm '{}'. Allowed: {}", algo, allowed.join(", "));
             }
 
-            // Password requirements
-            if algo == "aes" || algo == "chacha" {
-                if password.is_none() {
-                    anyhow::bail!("--password is required for AES and ChaCha20");
-                }
+            if algo == "zip" && text.is_some() {
+                anyhow::bail!("--text is not supported with zip (use --input and --output)");
             }
-            if algo == "xor" && password.is_some() {
-                anyhow::bail!("--password must NOT be used with XOR");
+            if (output_dir.is_some() || restore_metadata) && algo != "aes" && algo != "chacha" {
+                anyhow::bail!("--output-dir and --restore-metadata are only supported with aes and chacha");
             }
 
-            let data = io::read_file(&input)?;
-
+            // Password / key requirements
             match algo.as_str() {
-                "aes" => {
-                    let pwd = password.unwrap();
-                    let decrypted = aes::decrypt(&pwd, &data)?;
-                    io::write_file(&output, &decrypted)?;
+                "xor" => {
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with XOR");
+                    }
                 }
-                "chacha" => {
-                    let pwd = password.unwrap();
-                    let decrypted = chacha::decrypt(&pwd, &data)?;
-                    io::write_file(&output, &decrypted)?;
+                "fernet" => {
+                    if key.is_empty() {
+                        anyhow::bail!("--key is required for fernet");
+                    }
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with fernet (use --key)");
+                    }
                 }
-                "xor" => {
-                    let key = b"supersecretkey";
-                    let decrypted = xor::xor_decrypt(key, &data);
-                    io::write_file(&output, &decrypted)?;
+                "secretbox" | "secretstream" => {
+                    if key.len() != 1 {
+                        anyhow::bail!("{} needs exactly one --key (32 bytes as hex)", algo);
+                    }
+                    if password.is_some() {
+                        anyhow::bail!("--password must NOT be used with {} (use --key)", algo);
+                    }
                 }
-                _ => unreachable!(),
+                "jwe" => {
+                    if password.is_some() != key.is_empty() {
+                        anyhow::bail!("jwe needs either --password (PBES2) or --key (dir)");
+                    }
+                }
+                _ => {
+                    if password.is_none() {
+                        anyhow::bail!("--password is required for {}", algo);
+                    }
+                }
+            }
+
+            let mut data = match (&text, &input) {
+                (Some(t), _) => {
+                    let token = if t == "-" { io::read_stdin()? } else { t.as_bytes().to_vec() };
+                    if armor::is_armored(&token) || algo == "fernet" || algo == "jwe" || algo == "ansible-vault" {
+                        token
+                    } else {
+                        base64::decode(String::from_utf8_lossy(&token).trim())?
+                    }
+                }
+                (None, Some(input)) => io::read_file(input)?,
+                (None, None) => unreachable!(),
+            };
+
+            // Armored input is detected automatically
+            if armor::is_armored(&data) {
+                data = armor::dearmor(&data)?;
+            }
+
+            // ZIP: every entry is decrypted and authenticated before anything is written
+            if algo == "zip" {
+                let entries = zip::decrypt(&password.unwrap(), &data)?;
+
+                let output = output.unwrap();
+                let root = std::path::Path::new(&output);
+                for entry in &entries {
+                    let path = root.join(&entry.name);
+                    if entry.is_dir() {
+                        io::create_dir_all(&path)?;
+                        continue;
+                    }
+                    if let Some(parent) = path.parent() {
+                        io::create_dir_all(parent)?;
+                    }
+                    io::write_private(&path, &entry.data, force)?;
+                }
+
+                println!("[OK] Extracted {} entries using zip → {}", entries.len(), output);
+                return Ok(());
+            }
+
+            let (header, decrypted) =
+                decrypt_data(&algo, password.as_deref(), &key, &openssl_kdf, ttl, &data)?;
+            let codec = header.compression;
+
+            // Inline text: print the plaintext instead of writing a file
+            if text.is_some() {
+                compress::decompress(codec, &decrypted, io::stdout_writer())?;
+                return Ok(());
+            }
+
+            if (output_dir.is_some() || restore_metadata) && header.metadata.is_none() {
+                anyhow::bail!("No metadata stored in {} (encrypted without --store-metadata)", input.unwrap());
+            }
+
+            // --output-dir: the stored name is a bare file name (checked when the metadata is decoded)
+            let output = match (output, &output_dir) {
+                (Some(output), _) => output,
+                (None, Some(dir)) => {
+                    let Some(name) = header.metadata.as_ref().and_then(|m| m.name.as_deref()) else {
+                        anyhow::bail!("No file name stored in {}", input.unwrap());
+                    };
+                    io::create_dir_all(std::path::Path::new(dir))?;
+                    std::path::Path::new(dir).join(name).to_string_lossy().into_owned()
+                }
+                (None, None) => unreachable!(),
+            };
+
+            if codec == compress::Codec::None {
+                io::write_private(&output, &decrypted, force)?;
+            } else {
+                let mut writer = io::create_private_writer(&output, force)?;
+                compress::decompress(codec, &decrypted, &mut writer)?;
+                writer.commit()?;
+            }
+
+            if restore_metadata && let Some(metadata) = &header.metadata {
+                io::apply_metadata(std::path::Path::new(&output), metadata)?;
             }
 
             println!("[OK] Decrypted using {} → {}", algo, output);
@@ -156,33 +602,141 @@ pub fn run(args: Args) -> Result<()> {
         }
 
         // ----------------------------------------------------------------------
-        // BASE64 ENCODE
+        // ENCODE (base64, hex, base32, base58, ascii85, z85...)
         // ----------------------------------------------------------------------
-        Commands::Encode { algo, input, output } => {
+        Commands::Encode { algo, input, output, wrap, force } => {
 
-            if algo != "base64" {
-                anyhow::bail!("Invalid algorithm '{}'. Only base64 is supported", algo);
+            if !encoding::ALGOS.contains(&algo.as_str()) {
+                anyhow::bail!("Invalid algorithm '{}'. Allowed: {}", algo, encoding::ALGOS.join(", "));
             }
 
             if !std::path::Path::new(&input).exists() {
                 anyhow::bail!("Input file does not exist: {}", input);
             }
 
+            if wrap.is_some() && algo != "base64" {
+                anyhow::bail!("--wrap is only supported with base64");
+            }
+
+            if algo == "base64" {
+                let reader = io::open_reader(&input)?;
+                let mut writer = io::create_writer(&output, force)?;
+                base64::encode_stream(reader, &mut writer, wrap.unwrap_or(0))?;
+                writer.commit()?;
+            } else {
+                let data = io::read_file(&input)?;
+                let encoded = encoding::encode(&algo, &data)?;
+                io::write_file(&output, encoded.as_bytes(), force)?;
+            }
+
+            println!("[OK] Encoded using {} → {}", algo, output);
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // DECODE
+        // ----------------------------------------------------------------------
+        Commands::Decode { algo, input, output, ignore_garbage, force } => {
+
+            if !encoding::ALGOS.contains(&algo.as_str()) {
+                anyhow::bail!("Invalid algorithm '{}'. Allowed: {}", algo, encoding::ALGOS.join(", "));
+            }
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
+            if ignore_garbage && algo != "base64" {
+                anyhow::bail!("--ignore-garbage is only supported with base64");
+            }
+
+            if algo == "base64" {
+                let reader = io::open_reader(&input)?;
+                let mut writer = io::create_writer(&output, force)?;
+                base64::decode_stream(reader, &mut writer, ignore_garbage)?;
+                writer.commit()?;
+            } else {
+                let data = io::read_file(&input)?;
+                let text = String::from_utf8_lossy(&data);
+                let decoded = encoding::decode(&algo, &text)?;
+                io::write_file(&output, &decoded, force)?;
+            }
+
+            println!("[OK] Decoded using {} → {}", algo, output);
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // KEYGEN
+        // ----------------------------------------------------------------------
+        Commands::Keygen { algo } => {
+
+            match algo.as_str() {
+                "fernet" => println!("{}", fernet::generate_key()),
+                "jwe" => println!("{}", jwe::generate_key()),
+                "secretbox" | "secretstream" => println!("{}", sodium::generate_key()),
+                _ => anyhow::bail!("Invalid algorithm '{}'. Allowed: fernet, jwe, secretbox, secretstream", algo),
+            }
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // ROTATE (re-encrypt under the first key)
+        // ----------------------------------------------------------------------
+        Commands::Rotate { algo, input, output, key, password, new_password, force } => {
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
             let data = io::read_file(&input)?;
-            let encoded = base64::encode(&data);
-            io::write_file(&output, encoded.as_bytes())?;
 
-            println!("[OK] Base64 encoded → {}", output);
+            let rotated = match algo.as_str() {
+                "fernet" => {
+                    if key.is_empty() {
+                        anyhow::bail!("--key is required for fernet");
+                    }
+                    fernet::rotate(&key, &data)?
+                }
+                "ansible-vault" => {
+                    let (Some(old), Some(new)) = (password, new_password) else {
+                        anyhow::bail!("--password and --new-password are required for ansible-vault");
+                    };
+                    vault::rekey(&old, &new, &data)?
+                }
+                _ => anyhow::bail!("Invalid algorithm '{}'. Allowed: fernet, ansible-vault", algo),
+            };
+            io::write_file(&output, &rotated, force)?;
+
+            println!("[OK] Rotated using {} → {}", algo, output);
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // VIEW (print the plaintext, like `ansible-vault view`)
+        // ----------------------------------------------------------------------
+        Commands::View { algo, input, password } => {
+
+            if algo != "ansible-vault" {
+                anyhow::bail!("Invalid algorithm '{}'. Only ansible-vault is supported", algo);
+            }
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
+            let data = io::read_file(&input)?;
+            io::write_stdout(&SecretBytes::from(vault::decrypt(&password, &data)?))?;
             Ok(())
         }
 
         // ----------------------------------------------------------------------
-        // BASE64 DECODE
+        // MAC (authentication only)
         // ----------------------------------------------------------------------
-        Commands::Decode { algo, input, output } => {
+        Commands::Mac { algo, input, output, password, append, force } => {
 
-            if algo != "base64" {
-                anyhow::bail!("Invalid algorithm '{}'. Only base64 is supported", algo);
+            if !mac::ALGOS.contains(&algo.as_str()) {
+                anyhow::bail!("Invalid algorithm '{}'. Allowed: {}", algo, mac::ALGOS.join(", "));
             }
 
             if !std::path::Path::new(&input).exists() {
@@ -190,12 +744,281 @@ pub fn run(args: Args) -> Result<()> {
             }
 
             let data = io::read_file(&input)?;
-            let text = String::from_utf8_lossy(&data);
-            let decoded = base64::decode(&text)?;
-            io::write_file(&output, &decoded)?;
 
-            println!("[OK] Base64 decoded → {}", output);
+            if append {
+                let signed = mac::append_trailer(&algo, &password, &data)?;
+                io::write_file(&output, &signed, force)?;
+                println!("[OK] {} trailer appended → {}", algo, output);
+            } else {
+                let tag = mac::sign(&algo, &password, &data)?;
+                io::write_file(&output, format!("{}\n", tag).as_bytes(), force)?;
+                println!("[OK] {} tag written → {}", algo, output);
+            }
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // MAC VERIFY
+        // ----------------------------------------------------------------------
+        Commands::MacVerify { input, tag, password } => {
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
+            let data = io::read_file(&input)?;
+
+            match tag {
+                Some(tag_path) => {
+                    let tag_line = io::read_file(&tag_path)?;
+                    mac::verify(&password, &data, &String::from_utf8_lossy(&tag_line))?;
+                }
+                None => {
+                    let (content, tag_line) = mac::split_trailer(&data)?;
+                    mac::verify(&password, content, &tag_line)?;
+                }
+            }
+
+            println!("[OK] MAC valid: {}", input);
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // INSPECT
+        // ----------------------------------------------------------------------
+        Commands::Inspect { input, json } => {
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
+            let report = inspect::inspect(&io::read_file(&input)?);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+                return Ok(());
+            }
+
+            let foreign = if report.foreign { " (foreign format)" } else { "" };
+            println!("{:<14}: {}{}", "format", report.format, foreign);
+            let decrypt_with = match report.algos.as_slice() {
+                [] => "not supported by cryptor".to_string(),
+                algos => format!("--algo {}", algos.join(" | ")),
+            };
+            println!("{:<14}: {}", "decrypt with", decrypt_with);
+
+            for (key, value) in &report.fields {
+                match value {
+                    serde_json::Value::String(s) => println!("{:<14}: {}", key, s),
+                    serde_json::Value::Array(items) if items.is_empty() => println!("{:<14}: none", key),
+                    serde_json::Value::Array(items) => {
+                        println!("{:<14}:", key);
+                        for item in items {
+                            match item {
+                                serde_json::Value::String(s) => println!("{:<14}  - {}", "", s),
+                                serde_json::Value::Object(map) => {
+                                    let parts: Vec<String> = map
+                                        .iter()
+                                        .filter(|(_, v)| !v.is_null())
+                                        .map(|(k, v)| format!("{}: {}", k, v.as_str().map_or(v.to_string(), str::to_string)))
+                                        .collect();
+                                    println!("{:<14}  - {}", "", parts.join(", "));
+                                }
+                                other => println!("{:<14}  - {}", "", other),
+                            }
+                        }
+                    }
+                    other => println!("{:<14}: {}", key, other),
+                }
+            }
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // VERIFY
+        // ----------------------------------------------------------------------
+        Commands::Verify { algo, input, password, key } => {
+
+            if !std::path::Path::new(&input).exists() {
+                anyhow::bail!("Input file does not exist: {}", input);
+            }
+
+            let mut data = io::read_file(&input)?;
+            // Offsets are then those of the decoded data
+            if armor::is_armored(&data) {
+                data = armor::dearmor(&data)?;
+            }
+
+            let verdict = match (algo.as_str(), &password, &key) {
+                ("aes" | "chacha", Some(pwd), None) => verify::verify_native(&algo, pwd, &data)?,
+                ("secretstream", None, Some(key)) => sodium::secretstream_verify(&*sodium::parse_key(key)?, &data),
+                ("aes" | "chacha", _, _) => anyhow::bail!("{} needs --password (and no --key)", algo),
+                ("secretstream", _, _) => anyhow::bail!("secretstream needs --key (and no --password)"),
+                _ => anyhow::bail!("Invalid algorithm '{}'. Allowed: aes, chacha, secretstream", algo),
+            };
+
+            match verdict {
+                Verdict::Valid { chunks, bytes } => {
+                    println!("[OK] Authentic: {} ({} bytes, {} chunk(s), nothing written)", input, bytes, chunks);
+                    Ok(())
+                }
+                Verdict::Truncated { offset } => {
+                    anyhow::bail!("Truncated: {} ends at byte {}, before the end of the encrypted data", input, offset)
+                }
+                // One tag over the whole file: the damage cannot be located
+                Verdict::Tampered { .. } if algo != "secretstream" => anyhow::bail!(
+                    "Authentication failed for the whole of {} (modified or truncated data, or wrong password); \
+                     {} files carry a single tag, so the damaged bytes cannot be located",
+                    input, algo
+                ),
+                Verdict::Tampered { start, end } => anyhow::bail!(
+                    "Authentication failed for bytes {}..{} of {} (modified data, or wrong password / key)",
+                    start, end, input
+                ),
+            }
+        }
+
+        // ----------------------------------------------------------------------
+        // SELF-TEST
+        // ----------------------------------------------------------------------
+        Commands::SelfTest => {
+
+            let checks = selftest::run();
+            let failed = checks.iter().filter(|c| c.result.is_err()).count();
+
+            for check in &checks {
+                match &check.result {
+                    Ok(vectors) => println!("[PASS] {} ({} vector(s))", check.name, vectors),
+                    Err(e) => println!("[FAIL] {}: {}", check.name, e),
+                }
+            }
+
+            if failed > 0 {
+                anyhow::bail!("Self-test failed: {} of {} primitives", failed, checks.len());
+            }
+            println!("[OK] All {} self-tests passed", checks.len());
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // BENCH
+        // ----------------------------------------------------------------------
+        Commands::Bench { size, kdf_target_ms } => {
+
+            if size == 0 {
+                anyhow::bail!("--size must be at least 1 (MiB)");
+            }
+
+            let Some(bytes) = size.checked_mul(1024 * 1024) else {
+                anyhow::bail!("--size is too large ({} MiB)", size);
+            };
+
+            println!("Throughput ({} MiB in memory, no key derivation)", size);
+            for result in bench::throughput(bytes)? {
+                println!("  {:<36} {:>10.1} MiB/s", result.name, result.rate);
+            }
+
+            println!("Key derivation (once per file)");
+            for cost in bench::kdf_costs() {
+                println!("  {:<20} {:<44} {:>8.1} ms", cost.name, cost.setting, cost.elapsed.as_secs_f64() * 1000.0);
+            }
+
+            if let Some(ms) = kdf_target_ms {
+                let iterations = bench::calibrate(std::time::Duration::from_millis(ms));
+                println!("[OK] encrypt --kdf-target-ms {} would use {} PBKDF2 iterations", ms, iterations);
+            }
+            Ok(())
+        }
+
+        // ----------------------------------------------------------------------
+        // RANDOM
+        // ----------------------------------------------------------------------
+        Commands::Random { kind, length, format, charset, chars, separator } => {
+
+            if length == Some(0) {
+                anyhow::bail!("--length must be at least 1");
+            }
+
+            // Only the generated value goes to stdout, so it can be piped; the entropy goes to stderr
+            match kind.as_str() {
+                "bytes" => {
+                    let data = random::bytes(length.unwrap_or(32));
+                    match format.as_str() {
+                        "raw" => io::write_stdout(&data)?,
+                        "hex" => println!("{}", hex::encode(&*data)),
+                        "base64" => println!("{}", base64::encode(&data)),
+                        _ => anyhow::bail!("Invalid format '{}'. Allowed: raw, hex, base64", format),
+                    }
+                }
+                "password" => {
+                    let alphabet = random::alphabet(&charset, chars.as_deref())?;
+                    let length = length.unwrap_or(20);
+                    println!("{}", *random::password(&alphabet, length));
+                    eprintln!(
+                        "[OK] {} characters from {} symbols: {:.1} bits of entropy",
+                        length,
+                        alphabet.len(),
+                        random::entropy_bits(alphabet.len(), length)
+                    );
+                }
+                "passphrase" => {
+                    let words = random::wordlist();
+                    let length = length.unwrap_or(6);
+                    println!("{}", *random::passphrase(&words, length, &separator));
+                    eprintln!(
+                        "[OK] {} words from a list of {}: {:.1} bits of entropy",
+                        length,
+                        words.len(),
+                        random::entropy_bits(words.len(), length)
+                    );
+                }
+                _ => anyhow::bail!("Invalid kind '{}'. Allowed: bytes, password, passphrase", kind),
+            }
             Ok(())
         }
     }
 }
+
+// ----------------------------------------------------------------------
+// Decryption shared by DECRYPT and the encrypt --in-place / --shred check.
+// Returns the aes / chacha header (default for other algorithms) and the data, still compressed.
+// ----------------------------------------------------------------------
+fn decrypt_data(
+    algo: &str,
+    password: Option<&str>,
+    key: &[SecretString],
+    openssl_kdf: &str,
+    ttl: Option<u64>,
+    data: &[u8],
+) -> Result<(container::Header, SecretBytes)> {
+
+    let decrypted = match algo {
+        "aes" | "chacha" => {
+            let pwd = password.unwrap();
+            let (header, plaintext) = container::open(algo, pwd, data)?;
+            return Ok((header, SecretBytes::from(plaintext)));
+        }
+        "xor" => xor::xor_decrypt(xor::KEY, data),
+        "aes-256-cbc" | "aes-256-ctr" => {
+            let pwd = password.unwrap();
+            openssl::decrypt(algo, openssl_kdf, pwd, data)?
+        }
+        "gpg" => {
+            let pwd = password.unwrap();
+            openpgp::decrypt(pwd, data)?
+        }
+        "secretbox" => sodium::secretbox_decrypt(&*sodium::parse_key(&key[0])?, data)?,
+        "secretstream" => sodium::secretstream_decrypt(&*sodium::parse_key(&key[0])?, data)?,
+        "fernet" => fernet::decrypt(key, data, ttl)?,
+        "jwe" => match password {
+            Some(pwd) => jwe::decrypt(jwe::KeyManagement::Pbes2, pwd, data)?,
+            None => jwe::decrypt(jwe::KeyManagement::Dir, &key[0], data)?,
+        },
+        "ansible-vault" => {
+            let pwd = password.unwrap();
+            vault::decrypt(pwd, data)?
+        }
+        _ => unreachable!(),
+    };
+    Ok((container::Header::default(), SecretBytes::from(decrypted)))
+}
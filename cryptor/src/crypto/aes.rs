@@ -1,46 +1,53 @@
 use aes_gcm::{Aes256Gcm, Key, Nonce}; 
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
 use rand::rngs::OsRng;
 use rand::RngCore;
 use pbkdf2::pbkdf2_hmac;
 use sha2::Sha256;
 use anyhow::{Result, anyhow};
 
-const SALT_LEN: usize = 16;
-const NONCE_LEN: usize = 12;
-const KEY_LEN: usize = 32; // AES-256
-const PBKDF2_ITERS: u32 = 100_000;
+use crate::crypto::secret::SecretKey;
 
-fn derive_key(password: &str, salt: &[u8]) -> [u8; KEY_LEN] {
-    let mut key = [0u8; KEY_LEN];
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 12;
+pub const KEY_LEN: usize = 32; // AES-256
+pub const PBKDF2_ITERS: u32 = 100_000;
+
+pub fn derive_key(password: &str, salt: &[u8], iterations: u32) -> SecretKey<KEY_LEN> {
+    let mut key = SecretKey::zeroed();
     pbkdf2_hmac::<Sha256>(
         password.as_bytes(),
         salt,
-        PBKDF2_ITERS,
-        &mut key,
+        iterations,
+        &mut *key,
     );
     key
 }
 
 pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    encrypt_with_aad(password, PBKDF2_ITERS, &[], data)
+}
+
+pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    decrypt_with_aad(password, PBKDF2_ITERS, &[], data)
+}
+
+// Same format, with additional data authenticated alongside (e.g. a header stored in clear)
+// and a PBKDF2 iteration count other than the default (recorded in that header)
+pub fn encrypt_with_aad(password: &str, iterations: u32, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     // 1) Génération du salt
     let mut salt = [0u8; SALT_LEN];
     OsRng.fill_bytes(&mut salt);
 
     // 2) Dérivation de la clé
-    let key_bytes = derive_key(password, &salt);
-    let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
+    let key_bytes = derive_key(password, &salt, iterations);
 
     // 3) Génération du nonce
     let mut nonce_bytes = [0u8; NONCE_LEN];
     OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
 
     // 4) AES-GCM
-    let cipher = Aes256Gcm::new(key);
-    let ciphertext = cipher
-        .encrypt(nonce, data)
-        .map_err(|_| anyhow!("AES-GCM encryption failed"))?;
+    let ciphertext = seal(&key_bytes, &nonce_bytes, aad, data)?;
 
     // 5) Format final = salt || nonce || ciphertext
     let mut result = Vec::new();
@@ -51,7 +58,7 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
     Ok(result)
 }
 
-pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+pub fn decrypt_with_aad(password: &str, iterations: u32, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     if data.len() < SALT_LEN + NONCE_LEN {
         return Err(anyhow!("Invalid encrypted data"));
     }
@@ -62,16 +69,30 @@ pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
     let ciphertext = &data[SALT_LEN + NONCE_LEN..];
 
     // 2) Dérivation de la clé
-    let key_bytes = derive_key(password, salt);
-    let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
+    let key_bytes = derive_key(password, salt, iterations);
 
     // 3) AES-GCM
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Nonce::from_slice(nonce_bytes);
+    open(&key_bytes, nonce_bytes, aad, ciphertext)
+}
 
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| anyhow!("Invalid password or corrupted data"))?;
+// AES-256-GCM with a raw key and additional authenticated data;
+// returns ciphertext || tag (16 bytes)
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        return Err(anyhow!("Invalid AES-GCM nonce length"));
+    }
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .encrypt(Nonce::from_slice(nonce), Payload { msg: data, aad })
+        .map_err(|_| anyhow!("AES-GCM encryption failed"))
+}
 
-    Ok(plaintext)
+pub fn open(key: &[u8; KEY_LEN], nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        return Err(anyhow!("Invalid AES-GCM nonce length"));
+    }
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| anyhow!("Invalid password or corrupted data"))
 }
@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+
+use crate::crypto::base64;
+
+// ---------------------------
+// ASCII armor for encrypted files
+//
+// -----BEGIN <LABEL>-----
+// [Key: value headers, then a blank line]
+// <Base64, 64 columns>
+// =<Base64 of the CRC-24 of the binary data>
+// -----END <LABEL>-----
+// ---------------------------
+pub const CRYPTOR_MESSAGE: &str = "CRYPTOR MESSAGE";
+pub const PGP_MESSAGE: &str = "PGP MESSAGE";
+
+const BEGIN_PREFIX: &str = "-----BEGIN ";
+const LINE_WIDTH: usize = 64;
+
+// CRC-24 as used by OpenPGP armor (RFC 4880, section 6.1)
+const CRC24_INIT: u32 = 0xB704CE;
+const CRC24_POLY: u32 = 0x1864CFB;
+
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
+
+pub fn armor(label: &str, data: &[u8]) -> Result<String> {
+    let mut body = Vec::new();
+    base64::encode_stream(data, &mut body, LINE_WIDTH)?;
+
+    let crc = crc24(data).to_be_bytes();
+
+    let mut out = String::new();
+    // No armor headers, but the blank line that ends them is still required (RFC 4880, section 6.2)
+    out.push_str(&format!("-----BEGIN {}-----\n\n", label));
+    out.push_str(&String::from_utf8_lossy(&body));
+    out.push('=');
+    out.push_str(&base64::encode(&crc[1..]));
+    out.push('\n');
+    out.push_str(&format!("-----END {}-----\n", label));
+
+    Ok(out)
+}
+
+pub fn is_armored(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    data[start..].starts_with(BEGIN_PREFIX.as_bytes())
+}
+
+// "Key: value", the key being a single word
+fn is_armor_header(line: &str) -> bool {
+    line.split_once(": ")
+        .is_some_and(|(key, _)| !key.is_empty() && key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+}
+
+pub fn dearmor(data: &[u8]) -> Result<Vec<u8>> {
+    let text = std::str::from_utf8(data).map_err(|_| anyhow!("Invalid armored data"))?;
+
+    let mut lines = text.lines().map(str::trim).skip_while(|l| l.is_empty());
+    let label = lines
+        .next()
+        .and_then(|l| l.strip_prefix(BEGIN_PREFIX))
+        .and_then(|l| l.strip_suffix("-----"))
+        .ok_or_else(|| anyhow!("Missing armor header"))?;
+    let end = format!("-----END {}-----", label);
+
+    // Armor headers ("Version: ...", "Comment: ...") end with a blank line. Older cryptor
+    // versions wrote neither, so the Base64 may also start right after the BEGIN line.
+    let mut lines = lines.peekable();
+    let mut headers = 0;
+    while lines.next_if(|line| is_armor_header(line)).is_some() {
+        headers += 1;
+    }
+    if lines.next_if(|line| line.is_empty()).is_none() && headers > 0 {
+        return Err(anyhow!("Missing blank line after the armor headers"));
+    }
+
+    let mut body = String::new();
+    let mut checksum = None;
+    let mut ended = false;
+
+    for line in lines.by_ref() {
+        if line == end {
+            ended = true;
+            break;
+        }
+        if let Some(crc) = line.strip_prefix('=') {
+            checksum = Some(crc.to_string());
+        } else {
+            body.push_str(line);
+        }
+    }
+
+    if !ended {
+        return Err(anyhow!("Missing armor footer (truncated data?)"));
+    }
+    if lines.any(|line| !line.is_empty()) {
+        return Err(anyhow!("Unexpected data after the armor footer"));
+    }
+
+    let decoded = base64::decode(&body)?;
+
+    if let Some(crc) = checksum {
+        let expected = base64::decode(&crc)?;
+        if expected != crc24(&decoded).to_be_bytes()[1..] {
+            return Err(anyhow!("Armor checksum mismatch (corrupted data)"));
+        }
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4880, section 6.6
+    const RFC_SAMPLE: &str = "-----BEGIN PGP MESSAGE-----
+Version: OpenPrivacy 0.99
+
+yDgBO22WxBHv7O8X7O/jygAEzol56iUKiXmV+XmpCtmpqQUKiQrFqclFqUDBovzS
+vBSFjNSiVHsuAA==
+=njUN
+-----END PGP MESSAGE-----
+";
+
+    #[test]
+    fn crc24_known_values() {
+        assert_eq!(crc24(b""), 0xB704CE);
+        // CRC-24/OPENPGP check value
+        assert_eq!(crc24(b"123456789"), 0x21CF02);
+
+        let data = dearmor(RFC_SAMPLE.as_bytes()).unwrap();
+        assert_eq!(data.len(), 58);
+        assert_eq!(crc24(&data), 0x9E350D);
+    }
+
+    #[test]
+    fn dearmor_rejects_damage() {
+        let armored = armor(CRYPTOR_MESSAGE, b"some encrypted bytes").unwrap();
+        assert_eq!(dearmor(armored.as_bytes()).unwrap(), b"some encrypted bytes");
+        assert_eq!(dearmor(format!("\n{}\n\n", armored).as_bytes()).unwrap(), b"some encrypted bytes");
+
+        let bad_crc = RFC_SAMPLE.replace("=njUN", "=njUM");
+        assert!(dearmor(bad_crc.as_bytes()).unwrap_err().to_string().contains("checksum"));
+
+        let wrong_end = RFC_SAMPLE.replace("-----END PGP MESSAGE-----", "-----END PGP SIGNATURE-----");
+        assert!(dearmor(wrong_end.as_bytes()).is_err());
+
+        let trailing = format!("{}extra\n", RFC_SAMPLE);
+        assert!(dearmor(trailing.as_bytes()).unwrap_err().to_string().contains("after the armor footer"));
+
+        assert!(dearmor(&RFC_SAMPLE.as_bytes()[..RFC_SAMPLE.len() - 10]).is_err());
+        assert!(dearmor(b"no armor here").is_err());
+    }
+
+    #[test]
+    fn armor_headers() {
+        let armored = armor(PGP_MESSAGE, b"payload").unwrap();
+        assert!(armored.starts_with("-----BEGIN PGP MESSAGE-----\n\n"));
+
+        let body = armored.strip_prefix("-----BEGIN PGP MESSAGE-----\n\n").unwrap();
+        let with_headers = format!("-----BEGIN PGP MESSAGE-----\nVersion: GnuPG v2\nComment: a: b\n\n{}", body);
+        assert_eq!(dearmor(with_headers.as_bytes()).unwrap(), b"payload");
+
+        // Output of older versions, without the blank line
+        let legacy = format!("-----BEGIN PGP MESSAGE-----\n{}", body);
+        assert_eq!(dearmor(legacy.as_bytes()).unwrap(), b"payload");
+
+        let no_blank = format!("-----BEGIN PGP MESSAGE-----\nVersion: GnuPG v2\n{}", body);
+        assert!(dearmor(no_blank.as_bytes()).unwrap_err().to_string().contains("blank line"));
+    }
+}
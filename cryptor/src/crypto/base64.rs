@@ -1,6 +1,7 @@
 use anyhow::Result;
-use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
 use base64::Engine;
+use std::io::{Read, Write};
 
 pub fn encode(data: &[u8]) -> String {
     STANDARD.encode(data)
@@ -12,3 +13,209 @@ pub fn decode(data: &str) -> Result<Vec<u8>> {
         .map_err(|_| anyhow::anyhow!("Invalid Base64 data"))?;
     Ok(bytes)
 }
+
+// URL-safe alphabet ('-' and '_'), without '=' padding
+pub fn encode_url(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+pub fn decode_url(data: &str) -> Result<Vec<u8>> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|_| anyhow::anyhow!("Invalid URL-safe Base64 data"))?;
+    Ok(bytes)
+}
+
+// URL-safe alphabet with '=' padding (Fernet keys and tokens)
+pub fn encode_url_padded(data: &[u8]) -> String {
+    URL_SAFE.encode(data)
+}
+
+pub fn decode_url_padded(data: &str) -> Result<Vec<u8>> {
+    let bytes = URL_SAFE
+        .decode(data)
+        .map_err(|_| anyhow::anyhow!("Invalid URL-safe Base64 data"))?;
+    Ok(bytes)
+}
+
+// ---------------------------
+// Streaming (bounded memory)
+// ---------------------------
+
+// Multiple of 3 so that every chunk encodes without padding
+const ENCODE_CHUNK: usize = 3 * 16 * 1024;
+const DECODE_CHUNK: usize = 64 * 1024;
+
+// Encodes `reader` into `writer`, breaking lines every `wrap` characters
+// (76 for MIME, 64 for PEM, 0 for a single line)
+pub fn encode_stream<R: Read, W: Write>(mut reader: R, mut writer: W, wrap: usize) -> Result<()> {
+    let mut buf = vec![0u8; ENCODE_CHUNK];
+    let mut column = 0;
+
+    loop {
+        let n = read_full(&mut reader, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        let encoded = STANDARD.encode(&buf[..n]);
+        if wrap == 0 {
+            writer.write_all(encoded.as_bytes())?;
+        } else {
+            let mut rest = encoded.as_bytes();
+            while !rest.is_empty() {
+                let take = (wrap - column).min(rest.len());
+                writer.write_all(&rest[..take])?;
+                rest = &rest[take..];
+                column += take;
+                if column == wrap {
+                    writer.write_all(b"\n")?;
+                    column = 0;
+                }
+            }
+        }
+
+        if n < buf.len() {
+            break;
+        }
+    }
+
+    if wrap != 0 && column != 0 {
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Decodes `reader` into `writer`. Line breaks and whitespace are always skipped;
+// with `ignore_garbage`, any other character outside the alphabet is skipped too.
+pub fn decode_stream<R: Read, W: Write>(mut reader: R, mut writer: W, ignore_garbage: bool) -> Result<()> {
+    let mut buf = vec![0u8; DECODE_CHUNK];
+    let mut pending: Vec<u8> = Vec::with_capacity(DECODE_CHUNK + 4);
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        for &c in &buf[..n] {
+            if c.is_ascii_alphanumeric() || c == b'+' || c == b'/' || c == b'=' {
+                pending.push(c);
+            } else if !c.is_ascii_whitespace() && !ignore_garbage {
+                return Err(anyhow::anyhow!("Invalid Base64 data"));
+            }
+        }
+
+        // Decode every complete 4-character group, keep the remainder
+        let complete = pending.len() - pending.len() % 4;
+        if complete > 0 {
+            let decoded = STANDARD
+                .decode(&pending[..complete])
+                .map_err(|_| anyhow::anyhow!("Invalid Base64 data"))?;
+            writer.write_all(&decoded)?;
+            pending.drain(..complete);
+        }
+    }
+
+    if !pending.is_empty() {
+        let decoded = STANDARD
+            .decode(&pending)
+            .map_err(|_| anyhow::anyhow!("Invalid Base64 data"))?;
+        writer.write_all(&decoded)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(data: &[u8], wrap: usize) -> String {
+        let mut out = Vec::new();
+        encode_stream(data, &mut out, wrap).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn decoded(text: &[u8], ignore_garbage: bool) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        decode_stream(text, &mut out, ignore_garbage)?;
+        Ok(out)
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn wraps_lines() {
+        for wrap in [64, 76] {
+            let text = encoded(&sample(1000), wrap);
+            let lines: Vec<&str> = text.split_terminator('\n').collect();
+            assert!(text.ends_with('\n'));
+            assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == wrap));
+            assert_eq!(lines.concat(), encode(&sample(1000)));
+        }
+
+        // A full last line gets a single newline; empty input gets none
+        assert_eq!(encoded(&sample(48), 64), format!("{}\n", encode(&sample(48))));
+        assert_eq!(encoded(b"", 64), "");
+        assert_eq!(encoded(&sample(10), 0), encode(&sample(10)));
+    }
+
+    #[test]
+    fn chunk_boundaries() {
+        for len in [ENCODE_CHUNK - 1, ENCODE_CHUNK, ENCODE_CHUNK + 1, 2 * ENCODE_CHUNK + 1] {
+            let data = sample(len);
+            for wrap in [0, 76] {
+                let text = encoded(&data, wrap);
+                assert_eq!(text.replace('\n', ""), encode(&data), "{} bytes, wrap {}", len, wrap);
+                assert_eq!(decoded(text.as_bytes(), false).unwrap(), data, "{} bytes, wrap {}", len, wrap);
+            }
+        }
+
+        // Base64 text whose length falls around the decoder's read size
+        for chars in [DECODE_CHUNK - 4, DECODE_CHUNK, DECODE_CHUNK + 4] {
+            let data = sample(chars / 4 * 3);
+            let text = encode(&data);
+            assert_eq!(text.len(), chars);
+            assert_eq!(decoded(text.as_bytes(), false).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn garbage() {
+        let data = sample(100);
+        let crlf = encoded(&data, 76).replace('\n', "\r\n");
+        let junk = format!("*{}#\u{e9}!", crlf);
+
+        // Line breaks are always skipped, other characters only with ignore_garbage
+        assert_eq!(decoded(crlf.as_bytes(), false).unwrap(), data);
+        assert_eq!(decoded(junk.as_bytes(), true).unwrap(), data);
+        assert!(decoded(junk.as_bytes(), false).is_err());
+        assert!(decoded(b"QUJD*REVG", false).is_err());
+    }
+
+    #[test]
+    fn truncated_or_badly_padded() {
+        assert_eq!(decoded(b"QUJD", false).unwrap(), b"ABC");
+        assert_eq!(decoded(b"QQ==", false).unwrap(), b"A");
+        for text in [&b"QUJ"[..], b"Q", b"QQ=", b"QQ===", b"QUJD=", b"QQ==QUJD", b"Q===", b"=QQ="] {
+            assert!(decoded(text, false).is_err(), "{}", String::from_utf8_lossy(text));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::crypto::{aes, chacha, container, openpgp, openssl, sodium, vault, xor, zip};
+
+// ---------------------------
+// Speed on this host, for `bench` and encrypt --kdf-target-ms
+//
+// Ciphers are timed on an in-memory buffer with a fixed key (no file I/O, no key derivation);
+// key derivations with the cost and output length each format uses.
+// ---------------------------
+pub struct Throughput {
+    pub name: &'static str,
+    // MiB per second
+    pub rate: f64,
+}
+
+pub struct KdfCost {
+    pub name: &'static str,
+    pub setting: String,
+    pub elapsed: Duration,
+}
+
+type Cipher = fn(&[u8; 32], &[u8]) -> Result<Vec<u8>>;
+type Kdf = fn();
+
+const PASSWORD: &[u8] = b"correct horse battery staple";
+const SALT: [u8; 16] = [0x5a; 16];
+
+pub fn throughput(size: usize) -> Result<Vec<Throughput>> {
+    let ciphers: [(&'static str, Cipher); 5] = [
+        ("AES-256-GCM (aes)", |key, data| aes::seal(key, &[0; aes::NONCE_LEN], &[], data)),
+        ("ChaCha20-Poly1305 (chacha)", |key, data| chacha::seal(key, &[0; chacha::NONCE_LEN], &[], data)),
+        ("XSalsa20-Poly1305 (secretbox)", sodium::secretbox_encrypt),
+        ("XChaCha20-Poly1305 (secretstream)", sodium::secretstream_encrypt),
+        ("XOR", |_, data| Ok(xor::xor_encrypt(xor::KEY, data))),
+    ];
+
+    let key = [0x42u8; 32];
+    let data = vec![0u8; size];
+    ciphers
+        .into_iter()
+        .map(|(name, cipher)| {
+            let start = Instant::now();
+            cipher(&key, &data)?;
+            let secs = start.elapsed().as_secs_f64().max(1e-9);
+            Ok(Throughput { name, rate: size as f64 / (1024.0 * 1024.0) / secs })
+        })
+        .collect()
+}
+
+fn pbkdf2_sha256(iterations: u32, out: &mut [u8]) {
+    pbkdf2_hmac::<Sha256>(PASSWORD, &SALT, iterations, out);
+}
+
+fn time(kdf: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    kdf();
+    start.elapsed()
+}
+
+pub fn kdf_costs() -> Vec<KdfCost> {
+    // Output lengths matter: PBKDF2 runs once per block of hash output
+    let settings: [(&'static str, String, Kdf); 5] = [
+        ("aes / chacha / mac", format!("PBKDF2-HMAC-SHA256, {} iterations", aes::PBKDF2_ITERS), || {
+            pbkdf2_sha256(aes::PBKDF2_ITERS, &mut [0; aes::KEY_LEN])
+        }),
+        ("aes-256-cbc / -ctr", format!("PBKDF2-HMAC-SHA256, {} iterations", openssl::PBKDF2_ITERS), || {
+            pbkdf2_sha256(openssl::PBKDF2_ITERS, &mut [0; 48])
+        }),
+        ("ansible-vault", format!("PBKDF2-HMAC-SHA256, {} iterations", vault::PBKDF2_ITERS), || {
+            pbkdf2_sha256(vault::PBKDF2_ITERS, &mut [0; 80])
+        }),
+        ("zip", format!("PBKDF2-HMAC-SHA1, {} iterations", zip::PBKDF2_ITERS), || {
+            pbkdf2_hmac::<Sha1>(PASSWORD, &SALT, zip::PBKDF2_ITERS, &mut [0; 66])
+        }),
+        ("gpg", format!("S2K iterated SHA-256, {} bytes", openpgp::s2k_count(openpgp::S2K_COUNT)), || {
+            openpgp::s2k_hash::<Sha256>(&SALT[..8], openpgp::s2k_count(openpgp::S2K_COUNT), PASSWORD, 32);
+        }),
+    ];
+
+    settings
+        .into_iter()
+        .map(|(name, setting, kdf)| KdfCost { name, setting, elapsed: time(kdf) })
+        .collect()
+}
+
+// PBKDF2-HMAC-SHA256 iteration count for aes / chacha that takes about `target` on this host.
+// Never below the default (a short target or a slow host must not weaken files)
+// nor above what a header may record.
+pub fn calibrate(target: Duration) -> u32 {
+    // Grow a probe until it is long enough to time reliably, then scale linearly
+    let mut probe: u32 = 1_000;
+    let elapsed = loop {
+        let elapsed = time(|| pbkdf2_sha256(probe, &mut [0; aes::KEY_LEN]));
+        if elapsed >= Duration::from_millis(50) || probe >= container::MAX_ITERATIONS / 2 {
+            break elapsed;
+        }
+        probe *= 2;
+    };
+
+    let per_iteration = elapsed.as_secs_f64().max(1e-9) / probe as f64;
+    let iterations = (target.as_secs_f64() / per_iteration) as u64;
+    iterations.clamp(aes::PBKDF2_ITERS as u64, container::MAX_ITERATIONS as u64) as u32
+}
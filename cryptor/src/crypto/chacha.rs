@@ -1,28 +1,30 @@
 use chacha20poly1305::{
     ChaCha20Poly1305, Key, Nonce
 };
-use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
 
 use pbkdf2::pbkdf2_hmac;
 use sha2::Sha256;
 use anyhow::{Result, anyhow};
 use rand::RngCore;
 
+use crate::crypto::secret::SecretKey;
+
 const SALT_LEN: usize = 16;
-const NONCE_LEN: usize = 12;
-const KEY_LEN: usize = 32;
-const PBKDF2_ITERS: u32 = 100_000;
+pub const NONCE_LEN: usize = 12;
+pub const KEY_LEN: usize = 32;
+pub const PBKDF2_ITERS: u32 = 100_000;
 
 // ---------------------------
 // Key derivation (PBKDF2-HMAC-SHA256)
 // ---------------------------
-fn derive_key(password: &str, salt: &[u8]) -> [u8; KEY_LEN] {
-    let mut key = [0u8; KEY_LEN];
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> SecretKey<KEY_LEN> {
+    let mut key = SecretKey::zeroed();
     pbkdf2_hmac::<Sha256>(
         password.as_bytes(),
         salt,
-        PBKDF2_ITERS,
-        &mut key,
+        iterations,
+        &mut *key,
     );
     key
 }
@@ -32,24 +34,25 @@ fn derive_key(password: &str, salt: &[u8]) -> [u8; KEY_LEN] {
 // Output format:  salt || nonce || ciphertext
 // ---------------------------
 pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    encrypt_with_aad(password, PBKDF2_ITERS, &[], data)
+}
+
+// Same format, with additional data authenticated alongside (e.g. a header stored in clear)
+// and a PBKDF2 iteration count other than the default (recorded in that header)
+pub fn encrypt_with_aad(password: &str, iterations: u32, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     // Salt random
     let mut salt = [0u8; SALT_LEN];
     rand::thread_rng().fill_bytes(&mut salt);
 
     // Key derivation
-    let key_bytes = derive_key(password, &salt);
-    let key = Key::from_slice(&key_bytes);
+    let key_bytes = derive_key(password, &salt, iterations);
 
     // Nonce random
     let mut nonce_bytes = [0u8; NONCE_LEN];
     rand::thread_rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
 
     // Cipher
-    let cipher = ChaCha20Poly1305::new(key);
-    let ciphertext = cipher
-        .encrypt(nonce, data)
-        .map_err(|_| anyhow!("ChaCha20 encryption failed"))?;
+    let ciphertext = seal(&key_bytes, &nonce_bytes, aad, data)?;
 
     // salt || nonce || ciphertext
     let mut output = Vec::new();
@@ -64,6 +67,10 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
 // Decrypt
 // ---------------------------
 pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    decrypt_with_aad(password, PBKDF2_ITERS, &[], data)
+}
+
+pub fn decrypt_with_aad(password: &str, iterations: u32, aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
     if data.len() < SALT_LEN + NONCE_LEN {
         return Err(anyhow!("Corrupted ciphertext"));
     }
@@ -72,14 +79,30 @@ pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
     let nonce_bytes = &data[SALT_LEN..SALT_LEN + NONCE_LEN];
     let ciphertext = &data[SALT_LEN + NONCE_LEN..];
 
-    let key_bytes = derive_key(password, salt);
-    let key = Key::from_slice(&key_bytes);
-    let nonce = Nonce::from_slice(nonce_bytes);
+    let key_bytes = derive_key(password, salt, iterations);
+
+    open(&key_bytes, nonce_bytes, aad, ciphertext)
+}
 
-    let cipher = ChaCha20Poly1305::new(key);
-    let decrypted = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| anyhow!("Invalid password or corrupted data"))?;
+// ---------------------------
+// Raw ChaCha20-Poly1305 with a given key (no KDF)
+// ---------------------------
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        return Err(anyhow!("Invalid ChaCha20-Poly1305 nonce length"));
+    }
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .encrypt(Nonce::from_slice(nonce), Payload { msg: data, aad })
+        .map_err(|_| anyhow!("ChaCha20 encryption failed"))
+}
 
-    Ok(decrypted)
+pub fn open(key: &[u8; KEY_LEN], nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        return Err(anyhow!("Invalid ChaCha20-Poly1305 nonce length"));
+    }
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| anyhow!("Invalid password or corrupted data"))
 }
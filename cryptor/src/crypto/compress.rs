@@ -0,0 +1,79 @@
+use flate2::Compression;
+use flate2::read::{GzDecoder, GzEncoder};
+use anyhow::{Result, anyhow};
+use std::io::{self, Read, Write};
+
+// ---------------------------
+// Compression before encryption (aes / chacha)
+//
+// Warning: compression leaks information through the ciphertext length. If an attacker
+// can get their own data encrypted next to a secret (CRIME / BREACH style), or can see
+// the sizes of many similar messages, they can learn about the secret. Only compress
+// data that contains no attacker-controlled input.
+// ---------------------------
+pub const CODECS: [&str; 3] = ["none", "gzip", "zstd"];
+
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Codec {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "none" => Ok(Codec::None),
+            "gzip" => Ok(Codec::Gzip),
+            "zstd" => Ok(Codec::Zstd),
+            _ => Err(anyhow!("Invalid compression '{}'. Allowed: {}", name, CODECS.join(", "))),
+        }
+    }
+
+    // Identifier stored in the header
+    pub fn id(self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Gzip => 1,
+            Codec::Zstd => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Codec::None),
+            1 => Ok(Codec::Gzip),
+            2 => Ok(Codec::Zstd),
+            _ => Err(anyhow!("Unknown compression codec {}", id)),
+        }
+    }
+}
+
+// Reads and compresses the whole input; only the compressed data is kept in memory
+pub fn compress<R: Read>(codec: Codec, mut reader: R) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match codec {
+        Codec::None => {
+            reader.read_to_end(&mut out)?;
+        }
+        Codec::Gzip => {
+            GzEncoder::new(reader, Compression::default()).read_to_end(&mut out)?;
+        }
+        Codec::Zstd => zstd::stream::copy_encode(reader, &mut out, ZSTD_LEVEL)?,
+    }
+    Ok(out)
+}
+
+// Decompresses straight into `writer`, so the output never has to fit in memory
+pub fn decompress<W: Write>(codec: Codec, data: &[u8], mut writer: W) -> Result<()> {
+    let result = match codec {
+        Codec::None => writer.write_all(data),
+        Codec::Gzip => io::copy(&mut GzDecoder::new(data), &mut writer).map(|_| ()),
+        Codec::Zstd => zstd::stream::copy_decode(data, &mut writer),
+    };
+    result.map_err(|e| anyhow!("Decompression failed ({:?}): {}", codec, e))?;
+    Ok(writer.flush()?)
+}
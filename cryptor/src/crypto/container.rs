@@ -0,0 +1,220 @@
+use anyhow::{Result, anyhow};
+use zeroize::Zeroizing;
+
+use crate::crypto::compress::Codec;
+use crate::crypto::metadata::Metadata;
+use crate::crypto::padding::Padding;
+use crate::crypto::{aes, chacha};
+
+// ---------------------------
+// Native aes / chacha files
+//
+// Without options:  salt || nonce || ciphertext                  (original format)
+// With options:     header || salt || nonce || ciphertext
+//
+// header = "CRYPTOR" || version (1) || fields length (2, BE) || fields
+// field  = type (1) || length (1) || value
+//
+// The header is stored in clear but authenticated as AEAD additional data.
+// Files without the magic are read as the original format.
+//
+// The PBKDF2 iteration count is only recorded when it differs from the default
+// (encrypt --kdf-target-ms). It is capped at 10x the default, so a forged header can make a
+// wrong-password attempt cost at most about ten normal decryptions, not hours.
+//
+// Original file metadata is not in the header (the name would be visible): the header
+// only flags it, and the metadata block starts the encrypted payload, before the data.
+//
+// The payload is a single AEAD message, so it is not streamed: seal and open hold the whole
+// (compressed) plaintext and ciphertext in memory. Only decompression writes straight to the output.
+// ---------------------------
+const MAGIC: &[u8; 7] = b"CRYPTOR";
+const VERSION: u8 = 1;
+pub const PREFIX_LEN: usize = MAGIC.len() + 1 + 2;
+
+const FIELD_COMPRESSION: u8 = 0x01;
+const FIELD_PADDING: u8 = 0x02;
+const FIELD_METADATA: u8 = 0x03;
+const FIELD_KDF_ITERATIONS: u8 = 0x04;
+
+pub const MAX_ITERATIONS: u32 = 1_000_000;
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Header {
+    pub compression: Codec,
+    pub padding: Padding,
+    pub metadata: Option<Metadata>,
+    // PBKDF2 iterations, when not the algorithm's default
+    pub kdf_iterations: Option<u32>,
+}
+
+impl Header {
+    fn encode(&self) -> Vec<u8> {
+        let mut fields = Vec::new();
+        if self.compression != Codec::None {
+            fields.extend_from_slice(&[FIELD_COMPRESSION, 1, self.compression.id()]);
+        }
+        if self.padding != Padding::None {
+            let value = self.padding.encode();
+            fields.extend_from_slice(&[FIELD_PADDING, value.len() as u8]);
+            fields.extend_from_slice(&value);
+        }
+        if self.metadata.is_some() {
+            fields.extend_from_slice(&[FIELD_METADATA, 0]);
+        }
+        if let Some(iterations) = self.kdf_iterations {
+            fields.extend_from_slice(&[FIELD_KDF_ITERATIONS, 4]);
+            fields.extend_from_slice(&iterations.to_be_bytes());
+        }
+
+        let mut out = Vec::with_capacity(PREFIX_LEN + fields.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&(fields.len() as u16).to_be_bytes());
+        out.extend_from_slice(&fields);
+        out
+    }
+
+    // Returns None for files in the original format, or the header and its length
+    pub fn parse(data: &[u8]) -> Result<Option<(Header, usize)>> {
+        if !data.starts_with(MAGIC) {
+            return Ok(None);
+        }
+        if data.len() < PREFIX_LEN {
+            return Err(anyhow!("Corrupted header"));
+        }
+        if data[MAGIC.len()] != VERSION {
+            return Err(anyhow!("Unsupported file version {}", data[MAGIC.len()]));
+        }
+
+        let len = u16::from_be_bytes([data[MAGIC.len() + 1], data[MAGIC.len() + 2]]) as usize;
+        let fields = data
+            .get(PREFIX_LEN..PREFIX_LEN + len)
+            .ok_or_else(|| anyhow!("Corrupted header"))?;
+
+        let mut header = Header::default();
+        let mut pos = 0;
+        while pos < fields.len() {
+            let (kind, value_len) = match fields.get(pos..pos + 2) {
+                Some(&[kind, value_len]) => (kind, value_len as usize),
+                _ => return Err(anyhow!("Corrupted header")),
+            };
+            let value = fields
+                .get(pos + 2..pos + 2 + value_len)
+                .ok_or_else(|| anyhow!("Corrupted header"))?;
+
+            match (kind, value) {
+                (FIELD_COMPRESSION, &[id]) => header.compression = Codec::from_id(id)?,
+                (FIELD_PADDING, value) => header.padding = Padding::decode(value)?,
+                // Filled in from the payload once decrypted
+                (FIELD_METADATA, &[]) => header.metadata = Some(Metadata::default()),
+                (FIELD_KDF_ITERATIONS, &[a, b, c, d]) => {
+                    let iterations = u32::from_be_bytes([a, b, c, d]);
+                    if iterations == 0 || iterations > MAX_ITERATIONS {
+                        return Err(anyhow!("Invalid PBKDF2 iteration count {} in header", iterations));
+                    }
+                    header.kdf_iterations = Some(iterations);
+                }
+                _ => return Err(anyhow!("Unknown header field {} (written by a newer version?)", kind)),
+            }
+            pos += 2 + value_len;
+        }
+
+        Ok(Some((header, PREFIX_LEN + len)))
+    }
+}
+
+// `algo` is "aes" or "chacha"; the header is only written when it carries something
+pub fn seal(algo: &str, password: &str, header: &Header, data: &[u8]) -> Result<Vec<u8>> {
+    if *header == Header::default() {
+        return match algo {
+            "aes" => aes::encrypt(password, data),
+            "chacha" => chacha::encrypt(password, data),
+            _ => Err(anyhow!("Invalid algorithm '{}'", algo)),
+        };
+    }
+
+    let mut inner = match &header.metadata {
+        Some(metadata) => metadata.encode(),
+        None => Vec::new(),
+    };
+    inner.extend_from_slice(data);
+    let padded = Zeroizing::new(header.padding.pad(inner));
+
+    let mut out = header.encode();
+    let body = match algo {
+        "aes" => aes::encrypt_with_aad(password, header.kdf_iterations.unwrap_or(aes::PBKDF2_ITERS), &out, &padded)?,
+        "chacha" => {
+            chacha::encrypt_with_aad(password, header.kdf_iterations.unwrap_or(chacha::PBKDF2_ITERS), &out, &padded)?
+        }
+        _ => return Err(anyhow!("Invalid algorithm '{}'", algo)),
+    };
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+// Returns the header (with the metadata, if any) and the decrypted, unpadded (still compressed) data
+pub fn open(algo: &str, password: &str, data: &[u8]) -> Result<(Header, Vec<u8>)> {
+    let Some((mut header, len)) = Header::parse(data)? else {
+        let plaintext = match algo {
+            "aes" => aes::decrypt(password, data)?,
+            "chacha" => chacha::decrypt(password, data)?,
+            _ => return Err(anyhow!("Invalid algorithm '{}'", algo)),
+        };
+        return Ok((Header::default(), plaintext));
+    };
+
+    let (aad, body) = data.split_at(len);
+    let plaintext = match algo {
+        "aes" => aes::decrypt_with_aad(password, header.kdf_iterations.unwrap_or(aes::PBKDF2_ITERS), aad, body)?,
+        "chacha" => chacha::decrypt_with_aad(password, header.kdf_iterations.unwrap_or(chacha::PBKDF2_ITERS), aad, body)?,
+        _ => return Err(anyhow!("Invalid algorithm '{}'", algo)),
+    };
+    let mut plaintext = header.padding.unpad(plaintext)?;
+
+    if header.metadata.is_some() {
+        let (metadata, used) = Metadata::decode(&plaintext)?;
+        header.metadata = Some(metadata);
+        plaintext.drain(..used);
+    }
+    Ok((header, plaintext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_iterations(iterations: u32) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION, 0, 6, FIELD_KDF_ITERATIONS, 4]);
+        data.extend_from_slice(&iterations.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn kdf_iterations_roundtrip() {
+        for iterations in [1, aes::PBKDF2_ITERS, MAX_ITERATIONS] {
+            let header = Header { kdf_iterations: Some(iterations), ..Default::default() };
+            let encoded = header.encode();
+            assert_eq!(encoded, with_iterations(iterations));
+            assert_eq!(Header::parse(&encoded).unwrap(), Some((header, encoded.len())));
+        }
+
+        // The default count is not recorded
+        let encoded = Header::default().encode();
+        assert_eq!(Header::parse(&encoded).unwrap().unwrap().0.kdf_iterations, None);
+    }
+
+    #[test]
+    fn kdf_iterations_out_of_range_are_rejected() {
+        for iterations in [0, MAX_ITERATIONS + 1, u32::MAX] {
+            let err = Header::parse(&with_iterations(iterations)).unwrap_err();
+            assert!(err.to_string().contains("iteration count"), "{}: {}", iterations, err);
+        }
+
+        // Wrong value length
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION, 0, 5, FIELD_KDF_ITERATIONS, 3, 0, 0, 1]);
+        assert!(Header::parse(&data).is_err());
+    }
+}
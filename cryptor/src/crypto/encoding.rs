@@ -0,0 +1,221 @@
+use anyhow::{Result, anyhow};
+use data_encoding::{BASE32, BASE32HEX};
+
+use crate::crypto::base64;
+
+// ---------------------------
+// Binary-to-text encodings available to `encode` / `decode`
+// ---------------------------
+pub const ALGOS: [&str; 8] =
+    ["base64", "base64url", "hex", "base32", "base32hex", "base58", "ascii85", "z85"];
+
+pub fn encode(algo: &str, data: &[u8]) -> Result<String> {
+    match algo {
+        "base64" => Ok(base64::encode(data)),
+        "base64url" => Ok(base64::encode_url(data)),
+        "hex" => Ok(hex::encode(data)),
+        "base32" => Ok(BASE32.encode(data)),
+        "base32hex" => Ok(BASE32HEX.encode(data)),
+        "base58" => Ok(bs58::encode(data).into_string()),
+        "ascii85" => Ok(encode85(data, &ASCII85_ALPHABET, true)),
+        "z85" => {
+            if !data.len().is_multiple_of(4) {
+                return Err(anyhow!("Z85 input length must be a multiple of 4"));
+            }
+            Ok(encode85(data, &Z85_ALPHABET, false))
+        }
+        _ => Err(invalid_algo(algo)),
+    }
+}
+
+pub fn decode(algo: &str, text: &str) -> Result<Vec<u8>> {
+    let text = text.trim();
+    match algo {
+        "base64" => base64::decode(text),
+        "base64url" => base64::decode_url(text),
+        "hex" => hex::decode(text).map_err(|_| anyhow!("Invalid hex data")),
+        "base32" => BASE32
+            .decode(text.as_bytes())
+            .map_err(|_| anyhow!("Invalid Base32 data")),
+        "base32hex" => BASE32HEX
+            .decode(text.as_bytes())
+            .map_err(|_| anyhow!("Invalid Base32hex data")),
+        "base58" => bs58::decode(text)
+            .into_vec()
+            .map_err(|_| anyhow!("Invalid Base58 data")),
+        "ascii85" => {
+            let text = text.strip_prefix("<~").unwrap_or(text);
+            let text = text.strip_suffix("~>").unwrap_or(text);
+            decode85(text, &ASCII85_ALPHABET, true).ok_or_else(|| anyhow!("Invalid Ascii85 data"))
+        }
+        "z85" => {
+            if !text.len().is_multiple_of(5) {
+                return Err(anyhow!("Z85 input length must be a multiple of 5"));
+            }
+            decode85(text, &Z85_ALPHABET, false).ok_or_else(|| anyhow!("Invalid Z85 data"))
+        }
+        _ => Err(invalid_algo(algo)),
+    }
+}
+
+fn invalid_algo(algo: &str) -> anyhow::Error {
+    anyhow!("Invalid algorithm '{}'. Allowed: {}", algo, ALGOS.join(", "))
+}
+
+// ---------------------------
+// Base85 family (Ascii85 / Z85)
+// 4 bytes (big-endian u32) <-> 5 digits in base 85
+// ---------------------------
+const ASCII85_ALPHABET: [u8; 85] = {
+    let mut alphabet = [0u8; 85];
+    let mut i = 0;
+    while i < 85 {
+        alphabet[i] = b'!' + i as u8;
+        i += 1;
+    }
+    alphabet
+};
+
+const Z85_ALPHABET: [u8; 85] =
+    *b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+// `ascii85` enables the Adobe extensions: 'z' for a zero group and partial final groups
+fn encode85(data: &[u8], alphabet: &[u8; 85], ascii85: bool) -> String {
+    let mut out = String::with_capacity(data.len() / 4 * 5 + 5);
+
+    for chunk in data.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
+
+        if ascii85 && chunk.len() == 4 && value == 0 {
+            out.push('z');
+            continue;
+        }
+
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = alphabet[(value % 85) as usize];
+            value /= 85;
+        }
+
+        // A partial group of n bytes is written as n + 1 digits
+        for &d in &digits[..chunk.len() + 1] {
+            out.push(d as char);
+        }
+    }
+
+    out
+}
+
+fn decode85(text: &str, alphabet: &[u8; 85], ascii85: bool) -> Option<Vec<u8>> {
+    let mut lookup = [0xFFu8; 256];
+    for (i, &c) in alphabet.iter().enumerate() {
+        lookup[c as usize] = i as u8;
+    }
+
+    let mut out = Vec::with_capacity(text.len() / 5 * 4 + 4);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+
+    for c in text.bytes() {
+        if ascii85 && c.is_ascii_whitespace() {
+            continue;
+        }
+        if ascii85 && c == b'z' {
+            if len != 0 {
+                return None;
+            }
+            out.extend_from_slice(&[0, 0, 0, 0]);
+            continue;
+        }
+
+        let digit = lookup[c as usize];
+        if digit == 0xFF {
+            return None;
+        }
+
+        group[len] = digit;
+        len += 1;
+        if len == 5 {
+            out.extend_from_slice(&group_value(&group)?.to_be_bytes());
+            len = 0;
+        }
+    }
+
+    if len > 0 {
+        // Only Ascii85 allows a partial final group; pad it with the highest digit
+        if !ascii85 || len == 1 {
+            return None;
+        }
+        for digit in group.iter_mut().skip(len) {
+            *digit = 84;
+        }
+        out.extend_from_slice(&group_value(&group)?.to_be_bytes()[..len - 1]);
+    }
+
+    Some(out)
+}
+
+fn group_value(group: &[u8; 5]) -> Option<u32> {
+    group
+        .iter()
+        .try_fold(0u32, |acc, &d| acc.checked_mul(85)?.checked_add(d as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648 §10 inputs
+    const RFC4648: [&str; 7] = ["", "f", "fo", "foo", "foob", "fooba", "foobar"];
+
+    fn check(algo: &str, data: &[u8], text: &str) {
+        assert_eq!(encode(algo, data).unwrap(), text, "{} encode", algo);
+        assert_eq!(decode(algo, text).unwrap(), data, "{} decode", algo);
+    }
+
+    #[test]
+    fn rfc4648_vectors() {
+        let base32 = ["", "MY======", "MZXQ====", "MZXW6===", "MZXW6YQ=", "MZXW6YTB", "MZXW6YTBOI======"];
+        let base32hex = ["", "CO======", "CPNG====", "CPNMU===", "CPNMUOG=", "CPNMUOJ1", "CPNMUOJ1E8======"];
+        let base16 = ["", "66", "666f", "666f6f", "666f6f62", "666f6f6261", "666f6f626172"];
+        for (i, input) in RFC4648.iter().enumerate() {
+            check("base32", input.as_bytes(), base32[i]);
+            check("base32hex", input.as_bytes(), base32hex[i]);
+            check("hex", input.as_bytes(), base16[i]);
+        }
+        // The RFC writes Base16 in upper case; both cases decode
+        assert_eq!(decode("hex", "666F6F626172").unwrap(), b"foobar");
+        assert!(decode("base32", "MZXW6YT").is_err());
+    }
+
+    #[test]
+    fn z85_spec_vector() {
+        check("z85", &[0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B], "HelloWorld");
+        assert!(encode("z85", b"abc").is_err());
+        assert!(decode("z85", "Hell").is_err());
+    }
+
+    #[test]
+    fn ascii85_vectors() {
+        check("ascii85", b"Man is distinguished", "9jqo^BlbD-BleB1DJ+*+F(f,q");
+        check("ascii85", b"\0\0\0\0abc", "z@:E^");
+        let partial = ["", "Ac", "Ao@", "AoDS", "AoDTs", "AoDTs@/", "AoDTs@<)"];
+        for (input, text) in RFC4648.iter().zip(partial) {
+            check("ascii85", input.as_bytes(), text);
+        }
+        assert_eq!(decode("ascii85", "<~9jqo^BlbD-BleB1DJ+*+F(f,q~>").unwrap(), b"Man is distinguished");
+        assert!(decode("ascii85", "A").is_err());
+        // Above u32::MAX
+        assert!(decode("ascii85", "s8W-\"").is_err());
+    }
+
+    #[test]
+    fn base58_vectors() {
+        check("base58", b"Hello World!", "2NEpo7TZRRrLZSi2U");
+        // Each leading zero byte becomes a '1'
+        check("base58", &[0, 0, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4");
+        assert!(decode("base58", "0OIl").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use aes::Aes128;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::cipher::block_padding::Pkcs7;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+use crate::crypto::base64;
+use crate::crypto::secret::{SecretKey, SecretString};
+
+// ---------------------------
+// Fernet tokens (https://github.com/fernet/spec)
+// Key:    URL-safe Base64 of signing key (16) || encryption key (16)
+// Token:  URL-safe Base64 of 0x80 || timestamp (8) || IV (16) || AES-128-CBC ciphertext || HMAC-SHA256 (32)
+// ---------------------------
+const VERSION: u8 = 0x80;
+const KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+const HMAC_LEN: usize = 32;
+const HEADER_LEN: usize = 1 + 8 + IV_LEN;
+// Tokens dated further in the future than this are rejected (same as the Python library)
+const MAX_CLOCK_SKEW: u64 = 60;
+
+type Aes128CbcEnc = cbc::Encryptor<Aes128>;
+type Aes128CbcDec = cbc::Decryptor<Aes128>;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn parse_key(key: &str) -> Result<(SecretKey<16>, SecretKey<16>)> {
+    let bytes = Zeroizing::new(
+        base64::decode_url_padded(key.trim())
+            .map_err(|_| anyhow!("Invalid Fernet key (expected 32 bytes of URL-safe Base64)"))?,
+    );
+    if bytes.len() != KEY_LEN {
+        return Err(anyhow!("Invalid Fernet key (expected 32 bytes of URL-safe Base64)"));
+    }
+
+    let mut signing = SecretKey::zeroed();
+    let mut encryption = SecretKey::zeroed();
+    signing.copy_from_slice(&bytes[..16]);
+    encryption.copy_from_slice(&bytes[16..]);
+    Ok((signing, encryption))
+}
+
+pub fn generate_key() -> String {
+    let mut key = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    base64::encode_url_padded(&key)
+}
+
+fn encrypt_at(key: &str, data: &[u8], timestamp: u64, iv: &[u8; IV_LEN]) -> Result<Vec<u8>> {
+    let (signing, encryption) = parse_key(key)?;
+
+    let ciphertext = Aes128CbcEnc::new((&*encryption).into(), iv.into())
+        .encrypt_padded_vec_mut::<Pkcs7>(data);
+
+    let mut token = Vec::with_capacity(HEADER_LEN + ciphertext.len() + HMAC_LEN);
+    token.push(VERSION);
+    token.extend_from_slice(&timestamp.to_be_bytes());
+    token.extend_from_slice(iv);
+    token.extend_from_slice(&ciphertext);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&signing[..])
+        .map_err(|_| anyhow!("Invalid HMAC key"))?;
+    mac.update(&token);
+    token.extend_from_slice(&mac.finalize().into_bytes());
+
+    Ok(base64::encode_url_padded(&token).into_bytes())
+}
+
+// Returns the token (ASCII) for `data` under `key`
+pub fn encrypt(key: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let mut iv = [0u8; IV_LEN];
+    OsRng.fill_bytes(&mut iv);
+    encrypt_at(key, data, now(), &iv)
+}
+
+// Decrypts with the first key whose HMAC matches (MultiFernet); returns (plaintext, timestamp)
+fn open(keys: &[SecretString], token: &[u8], ttl: Option<u64>) -> Result<(Vec<u8>, u64)> {
+    open_at(keys, token, ttl, now())
+}
+
+// The timestamp is only checked against `current` when a TTL is given, as the spec does
+fn open_at(keys: &[SecretString], token: &[u8], ttl: Option<u64>, current: u64) -> Result<(Vec<u8>, u64)> {
+    let text = std::str::from_utf8(token).map_err(|_| anyhow!("Invalid Fernet token"))?;
+    let raw = base64::decode_url_padded(text.trim()).map_err(|_| anyhow!("Invalid Fernet token"))?;
+
+    if raw.len() < HEADER_LEN + HMAC_LEN || raw[0] != VERSION {
+        return Err(anyhow!("Invalid Fernet token"));
+    }
+
+    let mut ts_bytes = [0u8; 8];
+    ts_bytes.copy_from_slice(&raw[1..9]);
+    let timestamp = u64::from_be_bytes(ts_bytes);
+
+    if let Some(ttl) = ttl {
+        if timestamp.saturating_add(ttl) < current {
+            return Err(anyhow!("Fernet token has expired"));
+        }
+        if timestamp > current.saturating_add(MAX_CLOCK_SKEW) {
+            return Err(anyhow!("Fernet token timestamp is in the future"));
+        }
+    }
+
+    let (signed, tag) = raw.split_at(raw.len() - HMAC_LEN);
+    let iv = &raw[9..HEADER_LEN];
+    let ciphertext = &signed[HEADER_LEN..];
+
+    for key in keys {
+        let (signing, encryption) = parse_key(key)?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&signing[..])
+            .map_err(|_| anyhow!("Invalid HMAC key"))?;
+        mac.update(signed);
+        // verify_slice compares in constant time
+        if mac.verify_slice(tag).is_err() {
+            continue;
+        }
+
+        let plaintext = Aes128CbcDec::new((&*encryption).into(), iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map_err(|_| anyhow!("Invalid Fernet token"))?;
+        return Ok((plaintext, timestamp));
+    }
+
+    Err(anyhow!("Invalid key or corrupted token"))
+}
+
+pub fn decrypt(keys: &[SecretString], token: &[u8], ttl: Option<u64>) -> Result<Vec<u8>> {
+    open(keys, token, ttl).map(|(plaintext, _)| plaintext)
+}
+
+// Re-encrypts a token under the first key, keeping its original timestamp
+pub fn rotate(keys: &[SecretString], token: &[u8]) -> Result<Vec<u8>> {
+    let primary = keys
+        .first()
+        .ok_or_else(|| anyhow!("At least one Fernet key is required"))?;
+    let (plaintext, timestamp) = open(keys, token, None)?;
+    let plaintext = Zeroizing::new(plaintext);
+
+    let mut iv = [0u8; IV_LEN];
+    OsRng.fill_bytes(&mut iv);
+    encrypt_at(primary, &plaintext, timestamp, &iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors from the spec's generate.json / verify.json: 1985-10-26T01:20:00-07:00, IV 00..0f
+    const SECRET: &str = "cw_0x689RpI-jtRR7oE8h_eQsKImvJapLeSbXpwF4e4=";
+    const NOW: u64 = 499_162_800;
+    const TOKEN: &str = "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLLF_5CV9dOPmrhuVUPgJobwOz7JcbmrR64jVmpU4IwqDA==";
+
+    fn keys() -> Vec<SecretString> {
+        vec![SECRET.parse().unwrap()]
+    }
+
+    fn iv() -> [u8; IV_LEN] {
+        std::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn generate_vector() {
+        let token = encrypt_at(SECRET, b"hello", NOW, &iv()).unwrap();
+        assert_eq!(token, TOKEN.as_bytes());
+    }
+
+    #[test]
+    fn verify_vector() {
+        let (plaintext, timestamp) = open_at(&keys(), TOKEN.as_bytes(), Some(60), NOW + 1).unwrap();
+        assert_eq!(plaintext, b"hello");
+        assert_eq!(timestamp, NOW);
+    }
+
+    // The spec's invalid.json cases, rebuilt with the same secret, IV and timestamp
+    #[test]
+    fn invalid_vectors() {
+        let cases = [
+            ("incorrect mac", "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLLF_5CV9dOPmrhuVUPgJobwOz7JcbmrR64jVmpU4IwqDQ=="),
+            ("too short", "gAAAAAAdwJ6wAAECAwQFBgcICQo="),
+            ("invalid base64", "%%%%%%%%%%%%%AECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLLF_5CV9dOPmrhuVUPgJobwOz7JcbmrR64jVmpU4IwqDA=="),
+            ("payload size not multiple of block size", "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BMeTZDT7Rwa9JDJ2Oji9qpn0b4Yy5GwlkJZVdqiFG-Ul"),
+            ("payload padding error", "gAAAAAAdwJ6wAAECAwQFBgcICQoLDA0OD2F9vXhtlSApygZSE6tCiEr9O1HNXyJ6R2JN-YOFda154vQK8EU_SjbCdv3dSKrwzw=="),
+            ("far-future TS (unacceptable clock skew)", "gAAAAAAfodIwAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLJmPONM6K5SCpO6SECPrH9xldGO4xGxMu3dMoJe2Hy5aA=="),
+        ];
+        for (desc, token) in cases {
+            assert!(open_at(&keys(), token.as_bytes(), Some(60), NOW).is_err(), "{}", desc);
+        }
+
+        let err = open_at(&keys(), TOKEN.as_bytes(), Some(60), NOW + 61).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn clock_skew_only_matters_with_a_ttl() {
+        let future = "gAAAAAAfodIwAAECAwQFBgcICQoLDA0ODy021cpGVWKZ_eEwCGM4BLJmPONM6K5SCpO6SECPrH9xldGO4xGxMu3dMoJe2Hy5aA==";
+        assert_eq!(open_at(&keys(), future.as_bytes(), None, NOW).unwrap().0, b"hello");
+        assert!(open_at(&keys(), TOKEN.as_bytes(), Some(60), NOW - 60).is_ok());
+        assert!(open_at(&keys(), TOKEN.as_bytes(), Some(60), NOW - 61).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value, json};
+
+use crate::crypto::compress::Codec;
+use crate::crypto::padding::Padding;
+use crate::crypto::{aes, armor, base64, container, openpgp, openssl, sodium, vault, zip};
+
+// ---------------------------
+// Describes an encrypted file without any password: format, version, cipher, KDF and
+// parameters, salts / nonces, recipients, chunk size and payload length.
+//
+// Formats are recognized by their magic / structure. Native aes / chacha files without
+// a header, secretbox, secretstream and xor have no magic and are reported as unknown.
+// ---------------------------
+pub struct Report {
+    pub format: &'static str,
+    // Written by another tool (cryptor may still be able to decrypt it)
+    pub foreign: bool,
+    // --algo values that can decrypt it; empty when cryptor cannot
+    pub algos: Vec<&'static str>,
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+impl Report {
+    fn new(format: &'static str, foreign: bool, algos: &[&'static str]) -> Self {
+        Report { format, foreign, algos: algos.to_vec(), fields: Vec::new() }
+    }
+
+    fn field(&mut self, key: &'static str, value: impl Into<Value>) {
+        self.fields.push((key, value.into()));
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        map.insert("format".into(), json!(self.format));
+        map.insert("foreign".into(), json!(self.foreign));
+        map.insert("decrypt_with".into(), json!(self.algos));
+        for (key, value) in &self.fields {
+            map.insert(key.to_string(), value.clone());
+        }
+        Value::Object(map)
+    }
+}
+
+// Fills in the fields of a report once its format is known
+type Parser = fn(&[u8], &mut Report) -> Result<()>;
+
+// Never fails: a recognized format that does not parse gets an "error" field
+pub fn inspect(data: &[u8]) -> Report {
+    if armor::is_armored(data) {
+        return inspect_armored(data);
+    }
+
+    let text = std::str::from_utf8(data).map(str::trim).unwrap_or_default();
+
+    let (mut report, parse): (Report, Parser) = if data.starts_with(b"CRYPTOR") {
+        (Report::new("cryptor", false, &["aes", "chacha"]), native)
+    } else if data.starts_with(b"age-encryption.org/") {
+        (Report::new("age", true, &[]), age)
+    } else if data.starts_with(b"Salted__") {
+        (Report::new("openssl enc", true, &["aes-256-cbc", "aes-256-ctr"]), salted)
+    } else if data.starts_with(b"$ANSIBLE_VAULT") {
+        (Report::new("ansible-vault", true, &["ansible-vault"]), ansible_vault)
+    } else if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+        (Report::new("zip", true, &["zip"]), zip_archive)
+    } else if text.split('.').count() == 5 && base64::decode_url(text.split('.').next().unwrap()).is_ok() {
+        (Report::new("jwe", true, &["jwe"]), jwe)
+    } else if base64::decode_url_padded(text).is_ok_and(|t| t.first() == Some(&0x80)) {
+        (Report::new("fernet", true, &["fernet"]), fernet)
+    } else if openpgp::read_packets(data).is_ok_and(|p| p.first().is_some_and(|p| p.tag == 1 || p.tag == 3)) {
+        (Report::new("openpgp", true, &["gpg"]), pgp)
+    } else {
+        (Report::new("unknown", false, &["aes", "chacha", "secretbox", "secretstream", "xor"]), unknown)
+    };
+
+    report.field("size", data.len());
+    if let Err(e) = parse(data, &mut report) {
+        report.field("error", e.to_string());
+    }
+    report
+}
+
+fn inspect_armored(data: &[u8]) -> Report {
+    let label = String::from_utf8_lossy(data)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .and_then(|l| l.strip_prefix("-----BEGIN "))
+        .and_then(|l| l.strip_suffix("-----"))
+        .unwrap_or_default()
+        .to_string();
+
+    let mut report = match armor::dearmor(data) {
+        Ok(inner) => inspect(&inner),
+        Err(e) => {
+            let mut report = Report::new("unknown", false, &[]);
+            report.field("error", e.to_string());
+            report
+        }
+    };
+    report.fields.insert(0, ("armor", json!(label)));
+    report
+}
+
+fn take(data: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    pos.checked_add(len)
+        .and_then(|end| data.get(pos..end))
+        .ok_or_else(|| anyhow!("Truncated data"))
+}
+
+// ---------------------------
+// Native: "CRYPTOR" header || salt || nonce || ciphertext
+// ---------------------------
+fn native(data: &[u8], report: &mut Report) -> Result<()> {
+    let (header, len) = container::Header::parse(data)?.ok_or_else(|| anyhow!("Corrupted header"))?;
+
+    report.field("version", data[7]);
+    report.field("cipher", "AES-256-GCM or ChaCha20-Poly1305 (not recorded)");
+    report.field("kdf", "PBKDF2-HMAC-SHA256");
+    report.field("iterations", header.kdf_iterations.unwrap_or(aes::PBKDF2_ITERS));
+    report.field("compression", match header.compression {
+        Codec::None => "none",
+        Codec::Gzip => "gzip",
+        Codec::Zstd => "zstd",
+    });
+    report.field("padding", match header.padding {
+        Padding::None => "none".to_string(),
+        Padding::Padme => "padme".to_string(),
+        Padding::Block(size) => format!("block:{}", size),
+    });
+    report.field("metadata", if header.metadata.is_some() { "stored (encrypted)" } else { "none" });
+
+    let body = &data[len..];
+    report.field("salt", hex::encode(take(body, 0, aes::SALT_LEN)?));
+    report.field("nonce", hex::encode(take(body, aes::SALT_LEN, aes::NONCE_LEN)?));
+    report.field("payload_len", body.len() - aes::SALT_LEN - aes::NONCE_LEN);
+    Ok(())
+}
+
+// ---------------------------
+// age v1: text header (one stanza per recipient), MAC line, then 64 KiB STREAM chunks
+// ---------------------------
+fn age(data: &[u8], report: &mut Report) -> Result<()> {
+    let mut recipients = Vec::new();
+    let mut pos = 0;
+    let mut first = true;
+
+    loop {
+        let end = data[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|i| pos + i)
+            .ok_or_else(|| anyhow!("Truncated age header"))?;
+        let line = String::from_utf8_lossy(&data[pos..end]).into_owned();
+        pos = end + 1;
+
+        if first {
+            report.field("version", line.trim_start_matches("age-encryption.org/"));
+            first = false;
+        } else if let Some(stanza) = line.strip_prefix("-> ") {
+            let args: Vec<&str> = stanza.split(' ').collect();
+            match args[..] {
+                ["scrypt", salt, log_n] => {
+                    report.field("kdf", "scrypt");
+                    // Unpadded base64
+                    let padded = format!("{}{}", salt, "=".repeat((4 - salt.len() % 4) % 4));
+                    report.field("salt", hex::encode(base64::decode(&padded).unwrap_or_default()));
+                    report.field("work_factor", format!("2^{}", log_n));
+                    recipients.push("scrypt (password)".to_string());
+                }
+                [kind, tag, ..] if kind.starts_with("ssh-") => recipients.push(format!("{} (key tag {})", kind, tag)),
+                [kind, ..] => recipients.push(kind.to_string()),
+                [] => {}
+            }
+        } else if line.starts_with("---") {
+            break;
+        }
+    }
+
+    report.field("cipher", "ChaCha20-Poly1305 (STREAM)");
+    report.field("recipients", recipients);
+    report.field("nonce", hex::encode(take(data, pos, 16)?));
+    report.field("chunk_size", 64 * 1024);
+    report.field("payload_len", data.len() - pos - 16);
+    Ok(())
+}
+
+// ---------------------------
+// openssl enc: "Salted__" || salt (8) || ciphertext; neither cipher nor KDF is recorded
+// ---------------------------
+fn salted(data: &[u8], report: &mut Report) -> Result<()> {
+    report.field("cipher", "not recorded (cryptor: AES-256-CBC or AES-256-CTR)");
+    report.field(
+        "kdf",
+        format!("not recorded: PBKDF2-HMAC-SHA256 ({} iterations by default) or EVP_BytesToKey (MD5 / SHA-256)", openssl::PBKDF2_ITERS),
+    );
+    report.field("salt", hex::encode(take(data, 8, 8)?));
+    report.field("payload_len", data.len() - 16);
+    Ok(())
+}
+
+// ---------------------------
+// Ansible Vault: header line, then hex(hex(salt) \n hex(hmac) \n hex(ciphertext))
+// ---------------------------
+fn ansible_vault(data: &[u8], report: &mut Report) -> Result<()> {
+    let header = String::from_utf8_lossy(data).lines().next().unwrap_or_default().trim().to_string();
+    report.field("version", header.split(';').nth(1).unwrap_or_default());
+
+    let (vault_id, payload) = vault::parse_envelope(data)?;
+    if let Some(id) = vault_id {
+        report.field("vault_id", id);
+    }
+    report.field("cipher", "AES-256-CTR + HMAC-SHA256");
+    report.field("kdf", "PBKDF2-HMAC-SHA256");
+    report.field("iterations", vault::PBKDF2_ITERS);
+
+    let inner = String::from_utf8(hex::decode(payload)?)?;
+    let parts: Vec<&str> = inner.lines().collect();
+    let [salt, _hmac, ciphertext] = parts[..] else {
+        return Err(anyhow!("Corrupted Ansible Vault payload"));
+    };
+    report.field("salt", salt);
+    report.field("payload_len", ciphertext.len() / 2);
+    Ok(())
+}
+
+// ---------------------------
+// ZIP: one salt per WinZip AES entry
+// ---------------------------
+fn zip_archive(data: &[u8], report: &mut Report) -> Result<()> {
+    let listing = zip::list(data)?;
+
+    if listing.iter().any(|e| e.salt.is_some()) {
+        report.field("kdf", "PBKDF2-HMAC-SHA1");
+        report.field("iterations", zip::PBKDF2_ITERS);
+    }
+
+    let entries: Vec<Value> = listing
+        .iter()
+        .map(|e| {
+            json!({
+                "name": e.name,
+                "encryption": e.encryption,
+                "method": match e.method { 0 => "stored".to_string(), 8 => "deflate".to_string(), m => m.to_string() },
+                "salt": e.salt.as_ref().map(hex::encode),
+                "compressed": e.compressed,
+                "size": e.size,
+            })
+        })
+        .collect();
+    report.field("entries", entries);
+    report.field("payload_len", listing.iter().map(|e| e.compressed as u64).sum::<u64>());
+    Ok(())
+}
+
+// ---------------------------
+// JWE compact: header.encrypted_key.iv.ciphertext.tag
+// ---------------------------
+fn jwe(data: &[u8], report: &mut Report) -> Result<()> {
+    let text = std::str::from_utf8(data)?.trim();
+    let parts: Vec<&str> = text.split('.').collect();
+    let header: Value = serde_json::from_slice(&base64::decode_url(parts[0])?)
+        .map_err(|_| anyhow!("Invalid JWE header"))?;
+
+    report.field("cipher", header["enc"].clone());
+    report.field("key_management", header["alg"].clone());
+    if let Some(kid) = header.get("kid") {
+        report.field("recipients", vec![kid.clone()]);
+    }
+    if let Some(p2s) = header["p2s"].as_str() {
+        report.field("kdf", "PBKDF2");
+        report.field("iterations", header["p2c"].clone());
+        report.field("salt", hex::encode(base64::decode_url(p2s)?));
+    }
+    report.field("iv", hex::encode(base64::decode_url(parts[2])?));
+    report.field("payload_len", base64::decode_url(parts[3])?.len());
+    Ok(())
+}
+
+// ---------------------------
+// Fernet: version (0x80) || timestamp (8) || iv (16) || ciphertext || hmac (32)
+// ---------------------------
+fn fernet(data: &[u8], report: &mut Report) -> Result<()> {
+    let token = base64::decode_url_padded(std::str::from_utf8(data)?.trim())?;
+    if token.len() < 1 + 8 + 16 + 32 {
+        return Err(anyhow!("Truncated Fernet token"));
+    }
+
+    let mut timestamp = [0u8; 8];
+    timestamp.copy_from_slice(&token[1..9]);
+
+    report.field("version", format!("0x{:02x}", token[0]));
+    report.field("cipher", "AES-128-CBC + HMAC-SHA256");
+    report.field("timestamp", u64::from_be_bytes(timestamp));
+    report.field("iv", hex::encode(&token[9..25]));
+    report.field("payload_len", token.len() - 25 - 32);
+    Ok(())
+}
+
+// ---------------------------
+// OpenPGP: session key packets (one per password / public key), then the encrypted data
+// ---------------------------
+fn sym_name(id: u8) -> String {
+    match id {
+        2 => "3DES".into(),
+        3 => "CAST5".into(),
+        7 => "AES-128".into(),
+        8 => "AES-192".into(),
+        9 => "AES-256".into(),
+        10 => "Twofish".into(),
+        _ => format!("cipher {}", id),
+    }
+}
+
+fn hash_name(id: u8) -> String {
+    match id {
+        1 => "MD5".into(),
+        2 => "SHA-1".into(),
+        8 => "SHA-256".into(),
+        9 => "SHA-384".into(),
+        10 => "SHA-512".into(),
+        11 => "SHA-224".into(),
+        _ => format!("hash {}", id),
+    }
+}
+
+fn aead_name(id: u8) -> String {
+    match id {
+        1 => "EAX".into(),
+        2 => "OCB".into(),
+        3 => "GCM".into(),
+        _ => format!("AEAD {}", id),
+    }
+}
+
+fn s2k(spec: &[u8], report: &mut Report) -> Result<()> {
+    match take(spec, 0, 1)?[0] {
+        0 => report.field("kdf", format!("S2K simple ({})", hash_name(take(spec, 1, 1)?[0]))),
+        1 => {
+            report.field("kdf", format!("S2K salted ({})", hash_name(take(spec, 1, 1)?[0])));
+            report.field("salt", hex::encode(take(spec, 2, 8)?));
+        }
+        3 => {
+            report.field("kdf", format!("S2K iterated and salted ({})", hash_name(take(spec, 1, 1)?[0])));
+            report.field("salt", hex::encode(take(spec, 2, 8)?));
+            report.field("iterations", openpgp::s2k_count(take(spec, 10, 1)?[0]));
+        }
+        4 => {
+            let params = take(spec, 17, 3)?;
+            report.field("kdf", "Argon2");
+            report.field("salt", hex::encode(take(spec, 1, 16)?));
+            report.field("work_factor", format!("t={}, p={}, m=2^{} KiB", params[0], params[1], params[2]));
+        }
+        kind => report.field("kdf", format!("S2K type {}", kind)),
+    }
+    Ok(())
+}
+
+// Chunk size octet c: 2^(c + 6) bytes
+fn chunk_size(c: u8) -> Result<u64> {
+    1u64.checked_shl(c as u32 + 6).ok_or_else(|| anyhow!("Invalid chunk size {}", c))
+}
+
+fn pgp(data: &[u8], report: &mut Report) -> Result<()> {
+    let mut packets = Vec::new();
+    let mut recipients = Vec::new();
+
+    for packet in openpgp::read_packets(data)? {
+        let body = &packet.body;
+        let version = take(body, 0, 1)?[0];
+        match packet.tag {
+            1 => {
+                packets.push(format!("PKESK v{}", version));
+                let key_id = if version == 3 { hex::encode_upper(take(body, 1, 8)?) } else { "?".into() };
+                recipients.push(format!("public key {}", key_id));
+            }
+            3 => {
+                packets.push(format!("SKESK v{}", version));
+                recipients.push("password".to_string());
+                match version {
+                    4 => {
+                        report.field("cipher", sym_name(take(body, 1, 1)?[0]));
+                        s2k(&body[2..], report)?;
+                    }
+                    5 => {
+                        report.field("cipher", format!("{} ({})", sym_name(take(body, 1, 1)?[0]), aead_name(take(body, 2, 1)?[0])));
+                        s2k(&body[3..], report)?;
+                    }
+                    6 => {
+                        report.field("cipher", format!("{} ({})", sym_name(take(body, 2, 1)?[0]), aead_name(take(body, 3, 1)?[0])));
+                        s2k(body.get(5..).unwrap_or_default(), report)?;
+                    }
+                    _ => {}
+                }
+            }
+            9 => {
+                packets.push("SED (no integrity protection)".to_string());
+                report.field("payload_len", body.len());
+            }
+            18 => {
+                packets.push(format!("SEIPD v{}", version));
+                if version == 2 {
+                    report.field("chunk_size", chunk_size(take(body, 3, 1)?[0])?);
+                    report.field("salt", hex::encode(take(body, 4, 32)?));
+                    report.field("payload_len", body.len() - 36);
+                } else {
+                    report.field("payload_len", body.len() - 1);
+                }
+            }
+            20 => {
+                let mode = take(body, 2, 1)?[0];
+                let iv_len = match mode {
+                    1 => 16,
+                    2 => 15,
+                    _ => 12,
+                };
+                packets.push(format!("AEAD v{} ({})", version, aead_name(mode)));
+                report.field("chunk_size", chunk_size(take(body, 3, 1)?[0])?);
+                report.field("iv", hex::encode(take(body, 4, iv_len)?));
+                report.field("payload_len", body.len() - 4 - iv_len);
+            }
+            tag => packets.push(format!("packet {}", tag)),
+        }
+    }
+
+    report.field("packets", packets);
+    report.field("recipients", recipients);
+    Ok(())
+}
+
+// ---------------------------
+// No magic: only the layouts it could have
+// ---------------------------
+fn unknown(_: &[u8], report: &mut Report) -> Result<()> {
+    report.field(
+        "candidates",
+        vec![
+            format!("aes / chacha without header: salt ({}) || nonce ({}) || ciphertext", aes::SALT_LEN, aes::NONCE_LEN),
+            "secretbox: nonce (24) || ciphertext".to_string(),
+            format!("secretstream: header (24) || {}-byte chunks", sodium::CHUNK_LEN),
+            "xor".to_string(),
+        ],
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::fernet;
+    use std::path::Path;
+
+    fn fixture(path: &str) -> Vec<u8> {
+        std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data").join(path)).unwrap()
+    }
+
+    fn field<'a>(report: &'a Report, key: &str) -> Option<&'a Value> {
+        report.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    fn native_sample() -> Vec<u8> {
+        // zstd + 200000 iterations, then salt, nonce and a 32-byte ciphertext
+        let mut data = b"CRYPTOR\x01\x00\x09\x01\x01\x02\x04\x04".to_vec();
+        data.extend_from_slice(&200_000u32.to_be_bytes());
+        data.extend_from_slice(&[0xAA; aes::SALT_LEN + aes::NONCE_LEN + 32]);
+        data
+    }
+
+    fn age_sample() -> Vec<u8> {
+        let mut data = b"age-encryption.org/v1\n-> scrypt c2FsdHNhbHRzYWx0c2FsdA 18\nYWJj\n--- bWFj\n".to_vec();
+        data.extend_from_slice(&[7; 16 + 40]);
+        data
+    }
+
+    fn jwe_sample() -> Vec<u8> {
+        let header = json!({ "alg": "PBES2-HS256+A128KW", "enc": "A256GCM", "p2s": "AAECAwQFBgc", "p2c": 4096 });
+        format!("{}.AAAA.AAECAwQFBgcICQoL.AAECAw.AAAA", base64::encode_url(header.to_string().as_bytes())).into_bytes()
+    }
+
+    // (format, sample) for every recognized format
+    fn samples() -> Vec<(&'static str, Vec<u8>)> {
+        vec![
+            ("cryptor", native_sample()),
+            ("age", age_sample()),
+            ("openssl enc", fixture("openssl/cbc-pbkdf2.bin")),
+            ("ansible-vault", fixture("ansible-vault/vault-1.2.yml")),
+            ("zip", fixture("zip/aes.zip")),
+            ("jwe", jwe_sample()),
+            ("fernet", fernet::encrypt(&fernet::generate_key(), b"hello").unwrap()),
+            ("openpgp", fixture("openpgp/aes256-zip.gpg")),
+        ]
+    }
+
+    #[test]
+    fn recognizes_each_format() {
+        for (format, sample) in samples() {
+            let report = inspect(&sample);
+            assert_eq!(report.format, format);
+            assert!(field(&report, "error").is_none(), "{}: {:?}", format, field(&report, "error"));
+            assert!(field(&report, "payload_len").is_some() || format == "zip", "{}", format);
+        }
+    }
+
+    #[test]
+    fn reports_the_parameters() {
+        let native = inspect(&native_sample());
+        assert_eq!(field(&native, "compression"), Some(&json!("zstd")));
+        assert_eq!(field(&native, "iterations"), Some(&json!(200_000)));
+        assert_eq!(field(&native, "payload_len"), Some(&json!(32)));
+
+        let age = inspect(&age_sample());
+        assert_eq!(field(&age, "work_factor"), Some(&json!("2^18")));
+        assert_eq!(field(&age, "salt"), Some(&json!(hex::encode("saltsaltsaltsalt"))));
+        assert_eq!(field(&age, "payload_len"), Some(&json!(40)));
+
+        let vault = inspect(&fixture("ansible-vault/vault-1.2.yml"));
+        assert_eq!(field(&vault, "vault_id"), Some(&json!("prod")));
+
+        let jwe = inspect(&jwe_sample());
+        assert_eq!(field(&jwe, "iterations"), Some(&json!(4096)));
+        assert_eq!(field(&jwe, "salt"), Some(&json!("0001020304050607")));
+
+        let pgp = inspect(&fixture("openpgp/aes256-zip.gpg"));
+        assert_eq!(field(&pgp, "packets"), Some(&json!(["SKESK v4", "SEIPD v1"])));
+        assert_eq!(field(&pgp, "cipher"), Some(&json!("AES-256")));
+
+        let armored = inspect(&fixture("openpgp/aes256-zip.asc"));
+        assert_eq!(armored.format, "openpgp");
+        assert_eq!(field(&armored, "armor"), Some(&json!("PGP MESSAGE")));
+
+        assert_eq!(inspect(&[0x42; 100]).format, "unknown");
+    }
+
+    #[test]
+    fn truncated_inputs_do_not_panic() {
+        for (format, sample) in samples() {
+            for len in 0..sample.len() {
+                let report = inspect(&sample[..len]);
+                assert!(report.format == format || report.format == "unknown" || len < 16, "{} cut at {}", format, len);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_inputs_do_not_panic() {
+        for (_, mut sample) in samples() {
+            sample.extend_from_slice(&[0xFF; 64 * 1024]);
+            inspect(&sample);
+        }
+
+        // Length fields far beyond the data
+        let cases: [&[u8]; 5] = [
+            b"CRYPTOR\x01\xFF\xFF\x01",
+            &[0xC3, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 4, 9, 3],
+            // SEIPD v2 with chunk size octet 0xFF
+            &[0xC3, 0x04, 0x04, 0x09, 0x00, 0x08, 0xD2, 0x05, 0x02, 0x09, 0x03, 0xFF, 0x00],
+            // SKESK v6 cut after the AEAD octet
+            &[0xC3, 0x04, 0x06, 0x00, 0x09, 0x02],
+            b"Salted__\x01",
+        ];
+        for data in cases {
+            let report = inspect(data);
+            assert!(report.format == "unknown" || field(&report, "error").is_some(), "{:02x?}", data);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use aes_kw::{KekAes128, KekAes256};
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Sha256, Sha512};
+use serde_json::{Value, json};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use zeroize::Zeroizing;
+
+use crate::crypto::aes::{self, KEY_LEN, NONCE_LEN};
+use crate::crypto::base64;
+use crate::crypto::secret::SecretKey;
+
+// ---------------------------
+// JWE compact serialization (RFC 7516), content encryption A256GCM only
+// Token:  header . encrypted key . IV . ciphertext . tag   (each URL-safe Base64, no padding)
+// The ASCII of the encoded protected header is the AES-GCM additional data.
+// ---------------------------
+pub const ALGS: [&str; 3] = ["dir", "PBES2-HS256+A128KW", "PBES2-HS512+A256KW"];
+
+const ENC: &str = "A256GCM";
+const TAG_LEN: usize = 16;
+const P2S_LEN: usize = 16;
+// RFC 7518, section 4.8.1.1: the salt input must be at least 8 octets
+const MIN_P2S_LEN: usize = 8;
+const P2C: u32 = 100_000;
+// Refuse headers asking for an absurd amount of work
+const MAX_P2C: u64 = 10_000_000;
+
+fn parse_dir_key(key: &str) -> Result<SecretKey<KEY_LEN>> {
+    base64::decode_url(key.trim())
+        .ok()
+        .map(Zeroizing::new)
+        .and_then(|k| SecretKey::from_slice(&k))
+        .ok_or_else(|| anyhow!("Invalid JWE key (expected 32 bytes of URL-safe Base64)"))
+}
+
+// Kind of secret the caller holds: a raw key ("dir") or a password (PBES2).
+// `decrypt` only accepts tokens whose "alg" header matches it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyManagement {
+    Dir,
+    Pbes2,
+}
+
+impl KeyManagement {
+    fn accepts(self, alg: &str) -> bool {
+        match self {
+            KeyManagement::Dir => alg == "dir",
+            KeyManagement::Pbes2 => matches!(alg, "PBES2-HS256+A128KW" | "PBES2-HS512+A256KW"),
+        }
+    }
+}
+
+// Random key for "dir" (A256GCM needs 32 bytes)
+pub fn generate_key() -> String {
+    let mut key = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    base64::encode_url(&key)
+}
+
+// PBES2 (RFC 7518, section 4.8): salt = UTF8(alg) || 0x00 || p2s
+fn pbes2_kek(alg: &str, password: &str, p2s: &[u8], p2c: u32) -> Result<Zeroizing<Vec<u8>>> {
+    let mut salt = alg.as_bytes().to_vec();
+    salt.push(0);
+    salt.extend_from_slice(p2s);
+
+    match alg {
+        "PBES2-HS256+A128KW" => {
+            let mut kek = Zeroizing::new(vec![0u8; 16]);
+            pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, p2c, &mut kek);
+            Ok(kek)
+        }
+        "PBES2-HS512+A256KW" => {
+            let mut kek = Zeroizing::new(vec![0u8; 32]);
+            pbkdf2_hmac::<Sha512>(password.as_bytes(), &salt, p2c, &mut kek);
+            Ok(kek)
+        }
+        _ => Err(anyhow!("Unsupported JWE algorithm '{}'", alg)),
+    }
+}
+
+fn wrap(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>> {
+    let wrapped = match kek.len() {
+        16 => KekAes128::try_from(kek).ok().and_then(|k| k.wrap_vec(cek).ok()),
+        32 => KekAes256::try_from(kek).ok().and_then(|k| k.wrap_vec(cek).ok()),
+        _ => None,
+    };
+    wrapped.ok_or_else(|| anyhow!("AES key wrap failed"))
+}
+
+fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let cek = match kek.len() {
+        16 => KekAes128::try_from(kek).ok().and_then(|k| k.unwrap_vec(wrapped).ok()),
+        32 => KekAes256::try_from(kek).ok().and_then(|k| k.unwrap_vec(wrapped).ok()),
+        _ => None,
+    };
+    cek.map(Zeroizing::new)
+        .ok_or_else(|| anyhow!("Invalid password or corrupted data"))
+}
+
+// `secret` is the password for PBES2, or the URL-safe Base64 key for "dir"
+pub fn encrypt(alg: &str, secret: &str, data: &[u8]) -> Result<Vec<u8>> {
+    if !ALGS.contains(&alg) {
+        return Err(anyhow!("Invalid JWE algorithm '{}'. Allowed: {}", alg, ALGS.join(", ")));
+    }
+
+    let (header, cek, encrypted_key) = if alg == "dir" {
+        (json!({ "alg": alg, "enc": ENC }), parse_dir_key(secret)?, Vec::new())
+    } else {
+        let mut p2s = [0u8; P2S_LEN];
+        OsRng.fill_bytes(&mut p2s);
+        let kek = pbes2_kek(alg, secret, &p2s, P2C)?;
+
+        let mut cek = SecretKey::zeroed();
+        OsRng.fill_bytes(&mut *cek);
+
+        let header = json!({
+            "alg": alg,
+            "enc": ENC,
+            "p2s": base64::encode_url(&p2s),
+            "p2c": P2C,
+        });
+        let encrypted_key = wrap(&kek, &cek[..])?;
+        (header, cek, encrypted_key)
+    };
+
+    let protected = base64::encode_url(header.to_string().as_bytes());
+
+    let mut iv = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut iv);
+
+    let sealed = aes::seal(&cek, &iv, protected.as_bytes(), data)?;
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+
+    let token = [
+        protected,
+        base64::encode_url(&encrypted_key),
+        base64::encode_url(&iv),
+        base64::encode_url(ciphertext),
+        base64::encode_url(tag),
+    ]
+    .join(".");
+
+    Ok(token.into_bytes())
+}
+
+// `secret` is a password for KeyManagement::Pbes2, a URL-safe Base64 key for KeyManagement::Dir
+pub fn decrypt(mode: KeyManagement, secret: &str, token: &[u8]) -> Result<Vec<u8>> {
+    let text = std::str::from_utf8(token).map_err(|_| anyhow!("Invalid JWE token"))?;
+    let parts: Vec<&str> = text.trim().split('.').collect();
+    if parts.len() != 5 {
+        return Err(anyhow!("Invalid JWE token (expected 5 parts)"));
+    }
+
+    let header: Value = serde_json::from_slice(&base64::decode_url(parts[0])?)
+        .map_err(|_| anyhow!("Invalid JWE protected header"))?;
+    let encrypted_key = base64::decode_url(parts[1])?;
+
+    // The header is attacker-controlled: never let it pick how the secret is used
+    let alg = header["alg"].as_str().unwrap_or_default();
+    if !mode.accepts(alg) {
+        return Err(match mode {
+            KeyManagement::Dir => anyhow!("JWE algorithm '{}' does not match a direct key (expected dir)", alg),
+            KeyManagement::Pbes2 => anyhow!("JWE algorithm '{}' does not match a password (expected PBES2)", alg),
+        });
+    }
+
+    let cek: SecretKey<KEY_LEN> = match mode {
+        KeyManagement::Dir => {
+            if !encrypted_key.is_empty() {
+                return Err(anyhow!("Invalid JWE token (dir uses no encrypted key)"));
+            }
+            parse_dir_key(secret)?
+        }
+        KeyManagement::Pbes2 => {
+            let p2s = base64::decode_url(header["p2s"].as_str().unwrap_or_default())?;
+            if p2s.len() < MIN_P2S_LEN {
+                return Err(anyhow!("Invalid JWE p2s parameter (at least {} bytes)", MIN_P2S_LEN));
+            }
+            let p2c = header["p2c"]
+                .as_u64()
+                .filter(|&c| c > 0 && c <= MAX_P2C)
+                .ok_or_else(|| anyhow!("Invalid JWE p2c parameter"))?;
+
+            let kek = pbes2_kek(alg, secret, &p2s, p2c as u32)?;
+            SecretKey::from_slice(&unwrap(&kek, &encrypted_key)?)
+                .ok_or_else(|| anyhow!("Invalid JWE content encryption key"))?
+        }
+    };
+
+    open_content(&header, &cek, &parts)
+}
+
+// A256GCM layer: IV, ciphertext and tag, with the encoded header as additional data
+fn open_content(header: &Value, cek: &[u8; KEY_LEN], parts: &[&str]) -> Result<Vec<u8>> {
+    if header["enc"] != ENC {
+        return Err(anyhow!("Unsupported JWE content encryption {} (only {} is supported)", header["enc"], ENC));
+    }
+    let iv = base64::decode_url(parts[2])?;
+    let mut sealed = base64::decode_url(parts[3])?;
+    let tag = base64::decode_url(parts[4])?;
+    if tag.len() != TAG_LEN {
+        return Err(anyhow!("Invalid JWE authentication tag"));
+    }
+
+    sealed.extend_from_slice(&tag);
+    aes::open(cek, &iv, parts[0].as_bytes(), &sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 7516, appendix A.1 (RSAES-OAEP and AES GCM): content layer only, with the given CEK
+    const A1_TOKEN: &str = concat!(
+        "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ",
+        // The RSA-encrypted key is irrelevant here and left empty
+        "..",
+        "48V1_ALb6US04U3b.",
+        "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6jiSdiwkIr3ajwQzaBtQD_A.",
+        "XFBoMYUZodetZdvTiFvSkQ",
+    );
+    const A1_CEK: [u8; 32] = [
+        177, 161, 244, 128, 84, 143, 225, 115, 63, 180, 3, 255, 107, 154, 212, 246, 138, 7, 110, 91, 112, 46, 34, 105,
+        47, 130, 203, 46, 122, 234, 64, 252,
+    ];
+
+    #[test]
+    fn rfc7516_a1_content_layer() {
+        let parts: Vec<&str> = A1_TOKEN.split('.').collect();
+        let header: Value = serde_json::from_slice(&base64::decode_url(parts[0]).unwrap()).unwrap();
+        let plaintext = open_content(&header, &A1_CEK, &parts).unwrap();
+        assert_eq!(plaintext, b"The true sign of intelligence is not knowledge but imagination.");
+
+        // RSA-OAEP is neither a direct key nor a password
+        assert!(decrypt(KeyManagement::Dir, &base64::encode_url(&A1_CEK), A1_TOKEN.as_bytes()).is_err());
+        assert!(decrypt(KeyManagement::Pbes2, "password", A1_TOKEN.as_bytes()).is_err());
+    }
+
+    // RFC 7517, appendix C.4 / C.5: PBES2-HS256+A128KW key unwrap
+    #[test]
+    fn rfc7517_c_pbes2_unwrap() {
+        let p2s = base64::decode_url("2WCTcJZ1Rvd_CJuJripQ1w").unwrap();
+        let kek = pbes2_kek("PBES2-HS256+A128KW", "Thus from my lips, by yours, my sin is purged.", &p2s, 4096).unwrap();
+        assert_eq!(&kek[..], [110, 171, 169, 92, 129, 92, 109, 117, 233, 242, 116, 233, 170, 14, 24, 75]);
+
+        let wrapped = base64::decode_url("TrqXOwuNUfDV9VPTNbyGvEJ9JMjefAVn-TR1uIxR9p6hsRQh9Tk7BA").unwrap();
+        let cek = unwrap(&kek, &wrapped).unwrap();
+        assert_eq!(
+            &cek[..],
+            [
+                111, 27, 25, 52, 66, 29, 20, 78, 92, 176, 56, 240, 65, 208, 82, 112, 161, 131, 36, 55, 202, 236, 185,
+                172, 129, 23, 153, 194, 195, 48, 253, 182
+            ]
+        );
+    }
+
+    #[test]
+    fn alg_must_match_the_secret() {
+        let key = generate_key();
+        let dir = encrypt("dir", &key, b"hello").unwrap();
+        assert_eq!(decrypt(KeyManagement::Dir, &key, &dir).unwrap(), b"hello");
+        // A "dir" token must not turn a password into a raw key, nor the reverse
+        assert!(decrypt(KeyManagement::Pbes2, &key, &dir).is_err());
+
+        let pbes2 = encrypt("PBES2-HS512+A256KW", "secret", b"hello").unwrap();
+        assert_eq!(decrypt(KeyManagement::Pbes2, "secret", &pbes2).unwrap(), b"hello");
+        assert!(decrypt(KeyManagement::Dir, &key, &pbes2).is_err());
+    }
+
+    #[test]
+    fn short_p2s_is_rejected() {
+        let header = json!({ "alg": "PBES2-HS256+A128KW", "enc": ENC, "p2s": base64::encode_url(&[0u8; 7]), "p2c": 1000 });
+        let token = format!("{}.AAAA.AAAA.AAAA.AAAA", base64::encode_url(header.to_string().as_bytes()));
+        let err = decrypt(KeyManagement::Pbes2, "secret", token.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("p2s"));
+    }
+}
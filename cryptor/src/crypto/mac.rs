@@ -0,0 +1,173 @@
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+use subtle::ConstantTimeEq;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+
+use crate::crypto::aes::{derive_key, PBKDF2_ITERS, SALT_LEN};
+
+// ---------------------------
+// Authentication-only mode (no encryption)
+//
+// Tag format (one text line):  <algo>:<salt hex>:<tag hex>
+// Trailer format:              data || "\ncryptor-mac:" <algo>:<salt hex>:<tag hex> "\n"
+// ---------------------------
+pub const ALGOS: [&str; 3] = ["hmac-sha256", "hmac-sha512", "blake3"];
+
+const TRAILER_MARKER: &[u8] = b"\ncryptor-mac:";
+
+fn compute(algo: &str, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    match algo {
+        "hmac-sha256" => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key)
+                .map_err(|_| anyhow!("Invalid HMAC key"))?;
+            mac.update(data);
+            Ok(mac.finalize().into_bytes().to_vec())
+        }
+        "hmac-sha512" => {
+            let mut mac = Hmac::<Sha512>::new_from_slice(key)
+                .map_err(|_| anyhow!("Invalid HMAC key"))?;
+            mac.update(data);
+            Ok(mac.finalize().into_bytes().to_vec())
+        }
+        "blake3" => {
+            let key: [u8; 32] = key
+                .try_into()
+                .map_err(|_| anyhow!("BLAKE3 requires a 32-byte key"))?;
+            Ok(blake3::keyed_hash(&key, data).as_bytes().to_vec())
+        }
+        _ => Err(anyhow!(
+            "Invalid MAC algorithm '{}'. Allowed: {}",
+            algo,
+            ALGOS.join(", ")
+        )),
+    }
+}
+
+// Computes a tag over `data` and returns it as a single text line.
+pub fn sign(algo: &str, password: &str, data: &[u8]) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(password, &salt, PBKDF2_ITERS);
+    let tag = compute(algo, &key[..], data)?;
+
+    Ok(format!("{}:{}:{}", algo, hex::encode(salt), hex::encode(tag)))
+}
+
+// Checks `tag_line` (as produced by `sign`) against `data` in constant time.
+pub fn verify(password: &str, data: &[u8], tag_line: &str) -> Result<()> {
+    let mut parts = tag_line.trim().split(':');
+    let (algo, salt_hex, tag_hex) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(a), Some(s), Some(t), None) => (a, s, t),
+        _ => return Err(anyhow!("Malformed MAC tag")),
+    };
+
+    let salt = hex::decode(salt_hex).map_err(|_| anyhow!("Malformed MAC tag"))?;
+    let expected = hex::decode(tag_hex).map_err(|_| anyhow!("Malformed MAC tag"))?;
+
+    let key = derive_key(password, &salt, PBKDF2_ITERS);
+    let actual = compute(algo, &key[..], data)?;
+
+    if actual.ct_eq(&expected).into() {
+        Ok(())
+    } else {
+        Err(anyhow!("MAC verification failed (wrong password or modified data)"))
+    }
+}
+
+// Returns `data` followed by a readable trailer line holding the tag.
+pub fn append_trailer(algo: &str, password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let tag_line = sign(algo, password, data)?;
+
+    let mut output = data.to_vec();
+    output.extend_from_slice(TRAILER_MARKER);
+    output.extend_from_slice(tag_line.as_bytes());
+    output.push(b'\n');
+
+    Ok(output)
+}
+
+// Splits a file produced by `append_trailer` into (data, tag line).
+pub fn split_trailer(data: &[u8]) -> Result<(&[u8], String)> {
+    let pos = data
+        .windows(TRAILER_MARKER.len())
+        .rposition(|w| w == TRAILER_MARKER)
+        .ok_or_else(|| anyhow!("No MAC trailer found"))?;
+
+    let tag_line = String::from_utf8_lossy(&data[pos + TRAILER_MARKER.len()..]).into_owned();
+    Ok((&data[..pos], tag_line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flip(tag_line: &str, pos: usize) -> String {
+        let mut bytes = tag_line.as_bytes().to_vec();
+        bytes[pos] = if bytes[pos] == b'0' { b'1' } else { b'0' };
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn known_vectors() {
+        // RFC 4231, test case 2
+        let (key, data) = (b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex::encode(compute("hmac-sha256", key, data).unwrap()),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(compute("hmac-sha512", key, data).unwrap()),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+
+        // BLAKE3 test_vectors.json, keyed_hash of the empty input
+        assert_eq!(
+            hex::encode(compute("blake3", b"whats the Elvish word for friend", b"").unwrap()),
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26"
+        );
+        assert!(compute("blake3", b"short", b"").is_err());
+        assert!(compute("md5", b"key", b"").is_err());
+    }
+
+    #[test]
+    fn verify_rejects_any_flipped_bit() {
+        let data = b"quarterly report".to_vec();
+        let tag_line = sign("hmac-sha256", "pw", &data).unwrap();
+        verify("pw", &data, &tag_line).unwrap();
+
+        let mut modified = data.clone();
+        modified[3] ^= 0x01;
+        assert!(verify("pw", &modified, &tag_line).is_err());
+
+        // Last hex digit of the tag, then one of the salt
+        assert!(verify("pw", &data, &flip(&tag_line, tag_line.len() - 1)).is_err());
+        assert!(verify("pw", &data, &flip(&tag_line, "hmac-sha256:".len())).is_err());
+        assert!(verify("wrong", &data, &tag_line).is_err());
+        assert!(verify("pw", &data, "hmac-sha256:00").is_err());
+    }
+
+    #[test]
+    fn trailer_roundtrip() {
+        // The data may itself contain the marker: the last one is the trailer
+        let data = b"line one\ncryptor-mac:not a tag\nline two".to_vec();
+        let signed = append_trailer("blake3", "pw", &data).unwrap();
+        let (content, tag_line) = split_trailer(&signed).unwrap();
+        assert_eq!(content, &data[..]);
+        assert!(tag_line.starts_with("blake3:") && tag_line.ends_with('\n'));
+        verify("pw", content, &tag_line).unwrap();
+
+        // Shorter than the trailer, or no trailer at all
+        assert!(split_trailer(b"").is_err());
+        assert!(split_trailer(&TRAILER_MARKER[..TRAILER_MARKER.len() - 1]).is_err());
+        assert!(split_trailer(b"plain file\n").is_err());
+
+        // A trailer cut before its end no longer verifies
+        let cut = &signed[..signed.len() - 10];
+        let (content, tag_line) = split_trailer(cut).unwrap();
+        assert!(verify("pw", content, &tag_line).is_err());
+    }
+}
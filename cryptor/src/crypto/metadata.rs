@@ -0,0 +1,185 @@
+use std::path::{Component, Path};
+
+use anyhow::{Result, anyhow};
+
+// ---------------------------
+// Original file metadata, stored at the start of the encrypted payload (aes / chacha)
+//
+// block = length (4, BE) || entries
+// entry = type (1) || length (4, BE) || value
+//   0x01 file name (UTF-8, no directory)
+//   0x02 mode bits (4, BE)
+//   0x03 mtime: seconds since the epoch (8, BE, signed) || nanoseconds (4, BE)
+//   0x04 extended attribute: name length (2, BE) || name || value   (repeated)
+// ---------------------------
+const ENTRY_NAME: u8 = 0x01;
+const ENTRY_MODE: u8 = 0x02;
+const ENTRY_MTIME: u8 = 0x03;
+const ENTRY_XATTR: u8 = 0x04;
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub mode: Option<u32>,
+    // (seconds, nanoseconds) since the Unix epoch
+    pub mtime: Option<(i64, u32)>,
+    pub xattrs: Vec<(String, Vec<u8>)>,
+}
+
+fn put_entry(out: &mut Vec<u8>, kind: u8, value: &[u8]) {
+    out.push(kind);
+    out.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+fn take(data: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    pos.checked_add(len)
+        .and_then(|end| data.get(pos..end))
+        .ok_or_else(|| anyhow!("Corrupted metadata block"))
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// A bare file name: decrypting with --output-dir must not write outside that directory.
+// '\\' and ':' are refused on every platform, since the file may be decrypted on Windows.
+pub fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\\', ':', '\0'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+impl Metadata {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut entries = Vec::new();
+        if let Some(name) = &self.name {
+            put_entry(&mut entries, ENTRY_NAME, name.as_bytes());
+        }
+        if let Some(mode) = self.mode {
+            put_entry(&mut entries, ENTRY_MODE, &mode.to_be_bytes());
+        }
+        if let Some((secs, nanos)) = self.mtime {
+            let mut value = secs.to_be_bytes().to_vec();
+            value.extend_from_slice(&nanos.to_be_bytes());
+            put_entry(&mut entries, ENTRY_MTIME, &value);
+        }
+        for (name, value) in &self.xattrs {
+            let mut entry = (name.len() as u16).to_be_bytes().to_vec();
+            entry.extend_from_slice(name.as_bytes());
+            entry.extend_from_slice(value);
+            put_entry(&mut entries, ENTRY_XATTR, &entry);
+        }
+
+        let mut out = (entries.len() as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(&entries);
+        out
+    }
+
+    // Returns the metadata and the number of bytes it used
+    pub fn decode(data: &[u8]) -> Result<(Self, usize)> {
+        let len = be_u32(take(data, 0, 4)?) as usize;
+        let entries = take(data, 4, len)?;
+
+        let mut meta = Metadata::default();
+        let mut pos = 0;
+        while pos < entries.len() {
+            let kind = take(entries, pos, 1)?[0];
+            let value_len = be_u32(take(entries, pos + 1, 4)?) as usize;
+            let value = take(entries, pos + 5, value_len)?;
+            pos += 5 + value_len;
+
+            match kind {
+                ENTRY_NAME => {
+                    let name = String::from_utf8(value.to_vec())
+                        .ok()
+                        .filter(|n| is_plain_name(n))
+                        .ok_or_else(|| anyhow!("Corrupted metadata block (file name)"))?;
+                    meta.name = Some(name);
+                }
+                ENTRY_MODE if value.len() == 4 => meta.mode = Some(be_u32(value)),
+                ENTRY_MTIME if value.len() == 12 => {
+                    let mut secs = [0u8; 8];
+                    secs.copy_from_slice(&value[..8]);
+                    let nanos = be_u32(&value[8..]);
+                    if nanos >= 1_000_000_000 {
+                        return Err(anyhow!("Corrupted metadata block (modification time)"));
+                    }
+                    meta.mtime = Some((i64::from_be_bytes(secs), nanos));
+                }
+                ENTRY_MODE | ENTRY_MTIME => {
+                    return Err(anyhow!("Corrupted metadata block (malformed metadata entry {})", kind));
+                }
+                ENTRY_XATTR => {
+                    let name_len = u16::from_be_bytes([take(value, 0, 1)?[0], take(value, 1, 1)?[0]]) as usize;
+                    let name = String::from_utf8(take(value, 2, name_len)?.to_vec())
+                        .map_err(|_| anyhow!("Corrupted metadata block (xattr name)"))?;
+                    meta.xattrs.push((name, value[2 + name_len..].to_vec()));
+                }
+                _ => return Err(anyhow!("Unknown metadata entry {} (written by a newer version?)", kind)),
+            }
+        }
+
+        Ok((meta, 4 + len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_name(name: &str) -> Vec<u8> {
+        Metadata { name: Some(name.to_string()), ..Default::default() }.encode()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let meta = Metadata {
+            name: Some("rapport final.pdf".to_string()),
+            mode: Some(0o640),
+            mtime: Some((-1_234_567, 890)),
+            xattrs: vec![("user.tag".to_string(), b"blue".to_vec()), ("user.empty".to_string(), Vec::new())],
+        };
+        let mut block = meta.encode();
+        let len = block.len();
+        block.extend_from_slice(b"file data");
+
+        assert_eq!(Metadata::decode(&block).unwrap(), (meta, len));
+        assert_eq!(Metadata::decode(&Metadata::default().encode()).unwrap(), (Metadata::default(), 4));
+    }
+
+    #[test]
+    fn rejects_paths_and_empty_names() {
+        for name in ["../x", "a/b", "", ".", "..", "/etc/passwd", "nul\0byte", "..\\..\\x", "C:foo", "C:\\x", "a\\b", "./"] {
+            assert!(Metadata::decode(&with_name(name)).is_err(), "{:?}", name);
+        }
+        assert!(Metadata::decode(&with_name("..x")).is_ok());
+    }
+
+    #[test]
+    fn rejects_corrupted_blocks() {
+        let block = with_name("notes.txt");
+        assert!(Metadata::decode(&block[..block.len() - 1]).is_err());
+        assert!(Metadata::decode(&[0xFF, 0xFF, 0xFF, 0xFF]).is_err());
+
+        // mtime with 10^9 nanoseconds
+        let mut entries = vec![ENTRY_MTIME, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 0];
+        entries.extend_from_slice(&1_000_000_000u32.to_be_bytes());
+        let mut block = (entries.len() as u32).to_be_bytes().to_vec();
+        block.extend_from_slice(&entries);
+        assert!(Metadata::decode(&block).is_err());
+
+        // Known entries with the wrong length
+        for entries in [&[ENTRY_MODE, 0, 0, 0, 2, 1, 0xA4][..], &[ENTRY_MTIME, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 1]] {
+            let mut block = (entries.len() as u32).to_be_bytes().to_vec();
+            block.extend_from_slice(entries);
+            let err = Metadata::decode(&block).unwrap_err();
+            assert!(err.to_string().contains("malformed metadata entry"), "{}", err);
+        }
+
+        let mut unknown = 6u32.to_be_bytes().to_vec();
+        unknown.extend_from_slice(&[0x7F, 0, 0, 0, 1, 0]);
+        assert!(Metadata::decode(&unknown).is_err());
+    }
+}
@@ -1,4 +1,24 @@
 pub mod aes;
 pub mod chacha;
+pub mod container;
+pub mod compress;
+pub mod padding;
+pub mod metadata;
+pub mod sodium;
+pub mod openssl;
+pub mod openpgp;
+pub mod fernet;
+pub mod jwe;
+pub mod vault;
+pub mod zip;
 pub mod xor;
-pub mod base64;
\ No newline at end of file
+pub mod base64;
+pub mod encoding;
+pub mod armor;
+pub mod mac;
+pub mod inspect;
+pub mod verify;
+pub mod selftest;
+pub mod bench;
+pub mod random;
+pub mod secret;
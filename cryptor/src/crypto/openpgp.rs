@@ -0,0 +1,425 @@
+use aes::{Aes128, Aes192, Aes256};
+use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use subtle::ConstantTimeEq;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use std::io::Read;
+use zeroize::Zeroizing;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ---------------------------
+// OpenPGP symmetric encryption (RFC 4880), compatible with `gpg --symmetric`
+// Output format:  SKESK packet (tag 3) || SEIPD packet (tag 18)
+// ---------------------------
+const TAG_SKESK: u8 = 3;
+const TAG_COMPRESSED: u8 = 8;
+const TAG_SED: u8 = 9;
+const TAG_LITERAL: u8 = 11;
+const TAG_SEIPD: u8 = 18;
+
+const SYM_AES128: u8 = 7;
+const SYM_AES192: u8 = 8;
+const SYM_AES256: u8 = 9;
+
+const HASH_SHA1: u8 = 2;
+const HASH_SHA256: u8 = 8;
+const HASH_SHA512: u8 = 10;
+
+const S2K_SIMPLE: u8 = 0;
+const S2K_SALTED: u8 = 1;
+const S2K_ITERATED: u8 = 3;
+// Coded count: (16 + (c & 15)) << ((c >> 4) + 6) = 65011712 bytes, as gpg
+pub const S2K_COUNT: u8 = 0xFF;
+
+const BLOCK_LEN: usize = 16;
+const MDC_HEADER: [u8; 2] = [0xD3, 0x14];
+const MDC_LEN: usize = 22;
+
+pub struct Packet {
+    pub tag: u8,
+    pub body: Vec<u8>,
+}
+
+// ---------------------------
+// Packet framing
+// ---------------------------
+fn take(data: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    data.get(pos..pos + len)
+        .ok_or_else(|| anyhow!("Truncated OpenPGP packet"))
+}
+
+fn read_packet(data: &[u8], mut pos: usize) -> Result<(Packet, usize)> {
+    let ctb = take(data, pos, 1)?[0];
+    pos += 1;
+    if ctb & 0x80 == 0 {
+        return Err(anyhow!("Not an OpenPGP message"));
+    }
+
+    // New format: the length may be split into partial body chunks
+    if ctb & 0x40 != 0 {
+        let tag = ctb & 0x3F;
+        let mut body = Vec::new();
+        loop {
+            let b0 = take(data, pos, 1)?[0] as usize;
+            let (len, header, partial) = match b0 {
+                0..=191 => (b0, 1, false),
+                192..=223 => {
+                    let b1 = take(data, pos + 1, 1)?[0] as usize;
+                    (((b0 - 192) << 8) + b1 + 192, 2, false)
+                }
+                255 => {
+                    let b = take(data, pos + 1, 4)?;
+                    (u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize, 5, false)
+                }
+                _ => (1 << (b0 & 0x1F), 1, true),
+            };
+            pos += header;
+            body.extend_from_slice(take(data, pos, len)?);
+            pos += len;
+            if !partial {
+                return Ok((Packet { tag, body }, pos));
+            }
+        }
+    }
+
+    // Old format
+    let tag = (ctb >> 2) & 0x0F;
+    let len = match ctb & 0x03 {
+        0 => {
+            let b = take(data, pos, 1)?;
+            pos += 1;
+            b[0] as usize
+        }
+        1 => {
+            let b = take(data, pos, 2)?;
+            pos += 2;
+            u16::from_be_bytes([b[0], b[1]]) as usize
+        }
+        2 => {
+            let b = take(data, pos, 4)?;
+            pos += 4;
+            u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize
+        }
+        // Indeterminate length: up to the end of the data
+        _ => data.len() - pos,
+    };
+    let body = take(data, pos, len)?.to_vec();
+    Ok((Packet { tag, body }, pos + len))
+}
+
+pub fn read_packets(data: &[u8]) -> Result<Vec<Packet>> {
+    let mut packets = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let (packet, next) = read_packet(data, pos)?;
+        packets.push(packet);
+        pos = next;
+    }
+    Ok(packets)
+}
+
+fn write_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(0xC0 | tag);
+    match body.len() {
+        len @ 0..=191 => out.push(len as u8),
+        len @ 192..=8383 => {
+            let len = len - 192;
+            out.push((len >> 8) as u8 + 192);
+            out.push(len as u8);
+        }
+        len => {
+            out.push(0xFF);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(body);
+}
+
+// ---------------------------
+// String-to-key (S2K) key derivation
+// ---------------------------
+pub fn s2k_count(coded: u8) -> usize {
+    (16 + (coded as usize & 15)) << ((coded as usize >> 4) + 6)
+}
+
+pub fn s2k_hash<D: Digest>(salt: &[u8], count: usize, password: &[u8], key_len: usize) -> Zeroizing<Vec<u8>> {
+    let mut input = Zeroizing::new(salt.to_vec());
+    input.extend_from_slice(password);
+    let count = count.max(input.len());
+
+    // Hash the repeated salt || password in large blocks rather than byte by byte
+    let block = Zeroizing::new(input.repeat((64 * 1024 / input.len().max(1)).max(1)));
+
+    let mut key = Zeroizing::new(Vec::with_capacity(key_len));
+    let mut preload = 0;
+    while key.len() < key_len {
+        let mut hasher = D::new();
+        hasher.update(vec![0u8; preload]);
+
+        let mut remaining = count;
+        while remaining > 0 {
+            let n = remaining.min(block.len());
+            hasher.update(&block[..n]);
+            remaining -= n;
+        }
+
+        key.extend_from_slice(&hasher.finalize());
+        preload += 1;
+    }
+    key.truncate(key_len);
+    key
+}
+
+// Parses an S2K specifier and derives the key; returns (key, bytes consumed)
+fn s2k_derive(spec: &[u8], password: &str, key_len: usize) -> Result<(Zeroizing<Vec<u8>>, usize)> {
+    let kind = take(spec, 0, 1)?[0];
+    let hash = take(spec, 1, 1)?[0];
+    let password = password.as_bytes();
+
+    let (salt, count, used) = match kind {
+        S2K_SIMPLE => (&[][..], 0, 2),
+        S2K_SALTED => (take(spec, 2, 8)?, 0, 10),
+        S2K_ITERATED => (take(spec, 2, 8)?, s2k_count(take(spec, 10, 1)?[0]), 11),
+        _ => return Err(anyhow!("Unsupported OpenPGP S2K type {}", kind)),
+    };
+
+    let key = match hash {
+        HASH_SHA1 => s2k_hash::<Sha1>(salt, count, password, key_len),
+        HASH_SHA256 => s2k_hash::<Sha256>(salt, count, password, key_len),
+        HASH_SHA512 => s2k_hash::<Sha512>(salt, count, password, key_len),
+        _ => return Err(anyhow!("Unsupported OpenPGP S2K hash algorithm {}", hash)),
+    };
+    Ok((key, used))
+}
+
+// ---------------------------
+// AES in OpenPGP CFB mode (zero IV, no resynchronization)
+// ---------------------------
+fn key_len(sym: u8) -> Result<usize> {
+    match sym {
+        SYM_AES128 => Ok(16),
+        SYM_AES192 => Ok(24),
+        SYM_AES256 => Ok(32),
+        _ => Err(anyhow!("Unsupported OpenPGP cipher algorithm {} (only AES is supported)", sym)),
+    }
+}
+
+fn cfb(sym: u8, key: &[u8], buf: &mut [u8], encrypt: bool) -> Result<()> {
+    let iv = [0u8; BLOCK_LEN];
+    let bad_key = |_| anyhow!("Invalid OpenPGP session key");
+
+    macro_rules! run {
+        ($cipher:ty) => {
+            if encrypt {
+                cfb_mode::Encryptor::<$cipher>::new_from_slices(key, &iv).map_err(bad_key)?.encrypt(buf)
+            } else {
+                cfb_mode::Decryptor::<$cipher>::new_from_slices(key, &iv).map_err(bad_key)?.decrypt(buf)
+            }
+        };
+    }
+
+    match sym {
+        SYM_AES128 => run!(Aes128),
+        SYM_AES192 => run!(Aes192),
+        SYM_AES256 => run!(Aes256),
+        _ => return Err(anyhow!("Unsupported OpenPGP cipher algorithm {}", sym)),
+    }
+    Ok(())
+}
+
+// ---------------------------
+// Message contents
+// ---------------------------
+// A compressed packet can expand a thousandfold: stop well before exhausting memory
+const MAX_DECOMPRESSED: u64 = 1 << 30;
+
+fn decompress(body: &[u8], limit: u64) -> Result<Vec<u8>> {
+    let (&algo, compressed) = body
+        .split_first()
+        .ok_or_else(|| anyhow!("Truncated OpenPGP packet"))?;
+
+    // One byte past the limit tells a full buffer from an oversized one
+    let mut out = Vec::new();
+    match algo {
+        0 => compressed.take(limit + 1).read_to_end(&mut out)?,
+        1 => DeflateDecoder::new(compressed).take(limit + 1).read_to_end(&mut out)?,
+        2 => ZlibDecoder::new(compressed).take(limit + 1).read_to_end(&mut out)?,
+        _ => return Err(anyhow!("Unsupported OpenPGP compression algorithm {}", algo)),
+    };
+    if out.len() as u64 > limit {
+        return Err(anyhow!("OpenPGP compressed data exceeds {} bytes", limit));
+    }
+    Ok(out)
+}
+
+// Only one compression layer is accepted: gpg never nests them
+fn literal_data(packets: Vec<Packet>, limit: u64, compressed: bool) -> Result<Vec<u8>> {
+    for packet in packets {
+        match packet.tag {
+            TAG_LITERAL => {
+                // format (1) || name length (1) || name || date (4) || data
+                let name_len = take(&packet.body, 1, 1)?[0] as usize;
+                let start = 2 + name_len + 4;
+                take(&packet.body, 0, start)?;
+                return Ok(packet.body[start..].to_vec());
+            }
+            TAG_COMPRESSED => {
+                if compressed {
+                    return Err(anyhow!("Nested OpenPGP compressed packets are not supported"));
+                }
+                let inner = decompress(&packet.body, limit)?;
+                return literal_data(read_packets(&inner)?, limit, true);
+            }
+            // Marker, signature and one-pass signature packets carry no data
+            _ => continue,
+        }
+    }
+    Err(anyhow!("No literal data in OpenPGP message"))
+}
+
+pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    // 1) SKESK v4: AES-256, iterated and salted S2K with SHA-256
+    let mut salt = [0u8; 8];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut skesk = vec![4, SYM_AES256, S2K_ITERATED, HASH_SHA256];
+    skesk.extend_from_slice(&salt);
+    skesk.push(S2K_COUNT);
+    let (key, _) = s2k_derive(&skesk[2..], password, key_len(SYM_AES256)?)?;
+
+    // 2) Literal data packet (binary, no file name)
+    let date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0);
+    let mut literal = vec![b'b', 0];
+    literal.extend_from_slice(&date.to_be_bytes());
+    literal.extend_from_slice(data);
+
+    // 3) Random prefix (last two bytes repeated) || literal packet || MDC
+    let mut plaintext = vec![0u8; BLOCK_LEN];
+    OsRng.fill_bytes(&mut plaintext);
+    plaintext.extend_from_within(BLOCK_LEN - 2..);
+    write_packet(&mut plaintext, TAG_LITERAL, &literal);
+    plaintext.extend_from_slice(&MDC_HEADER);
+    let mdc = Sha1::digest(&plaintext);
+    plaintext.extend_from_slice(&mdc);
+
+    cfb(SYM_AES256, &key, &mut plaintext, true)?;
+
+    let mut seipd = vec![1];
+    seipd.extend_from_slice(&plaintext);
+
+    let mut output = Vec::new();
+    write_packet(&mut output, TAG_SKESK, &skesk);
+    write_packet(&mut output, TAG_SEIPD, &seipd);
+
+    Ok(output)
+}
+
+pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let packets = read_packets(data)?;
+
+    let skesk = packets
+        .iter()
+        .find(|p| p.tag == TAG_SKESK)
+        .ok_or_else(|| anyhow!("No password-encrypted session key in OpenPGP message"))?;
+
+    if packets.iter().any(|p| p.tag == TAG_SED) {
+        return Err(anyhow!("OpenPGP data without integrity protection (MDC) is not supported"));
+    }
+    let seipd = packets
+        .iter()
+        .find(|p| p.tag == TAG_SEIPD)
+        .ok_or_else(|| anyhow!("No encrypted data in OpenPGP message"))?;
+
+    // 1) Session key from the SKESK packet
+    if take(&skesk.body, 0, 1)?[0] != 4 {
+        return Err(anyhow!("Unsupported OpenPGP SKESK version"));
+    }
+    let s2k_sym = take(&skesk.body, 1, 1)?[0];
+    let (s2k_key, used) = s2k_derive(&skesk.body[2..], password, key_len(s2k_sym)?)?;
+    let encrypted_key = &skesk.body[2 + used..];
+
+    let (sym, key) = if encrypted_key.is_empty() {
+        (s2k_sym, s2k_key)
+    } else {
+        let mut session = Zeroizing::new(encrypted_key.to_vec());
+        cfb(s2k_sym, &s2k_key, &mut session, false)?;
+        let sym = session[0];
+        if session.len() != 1 + key_len(sym)? {
+            return Err(anyhow!("Invalid password or corrupted data"));
+        }
+        (sym, Zeroizing::new(session[1..].to_vec()))
+    };
+
+    // 2) SEIPD v1
+    if take(&seipd.body, 0, 1)?[0] != 1 {
+        return Err(anyhow!("Unsupported OpenPGP SEIPD version"));
+    }
+    let mut plaintext = seipd.body[1..].to_vec();
+    if plaintext.len() < BLOCK_LEN + 2 + MDC_LEN {
+        return Err(anyhow!("Corrupted ciphertext"));
+    }
+    cfb(sym, &key, &mut plaintext, false)?;
+
+    if plaintext[BLOCK_LEN - 2..BLOCK_LEN] != plaintext[BLOCK_LEN..BLOCK_LEN + 2] {
+        return Err(anyhow!("Invalid password or corrupted data"));
+    }
+
+    // 3) Modification detection code: SHA-1 over everything before the hash
+    let mdc_start = plaintext.len() - MDC_LEN;
+    if plaintext[mdc_start..mdc_start + 2] != MDC_HEADER {
+        return Err(anyhow!("Invalid password or corrupted data"));
+    }
+    let expected = Sha1::digest(&plaintext[..mdc_start + 2]);
+    if !bool::from(expected.as_slice().ct_eq(&plaintext[mdc_start + 2..])) {
+        return Err(anyhow!("Invalid password or corrupted data"));
+    }
+
+    literal_data(read_packets(&plaintext[BLOCK_LEN + 2..mdc_start])?, MAX_DECOMPRESSED, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::DeflateEncoder;
+    use std::io::Write;
+
+    fn literal(data: &[u8]) -> Vec<u8> {
+        let mut body = vec![b'b', 0, 0, 0, 0, 0];
+        body.extend_from_slice(data);
+        let mut out = Vec::new();
+        write_packet(&mut out, TAG_LITERAL, &body);
+        out
+    }
+
+    fn deflated(packets: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(vec![1], Compression::default());
+        encoder.write_all(packets).unwrap();
+        let mut out = Vec::new();
+        write_packet(&mut out, TAG_COMPRESSED, &encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn decompression_is_capped() {
+        let message = deflated(&literal(&[0u8; 4096]));
+        assert_eq!(literal_data(read_packets(&message).unwrap(), 8192, false).unwrap().len(), 4096);
+
+        let err = literal_data(read_packets(&message).unwrap(), 1024, false).unwrap_err();
+        assert!(err.to_string().contains("exceeds"));
+    }
+
+    #[test]
+    fn nested_compression_is_rejected() {
+        let message = deflated(&deflated(&literal(b"hello")));
+        let err = literal_data(read_packets(&message).unwrap(), MAX_DECOMPRESSED, false).unwrap_err();
+        assert!(err.to_string().contains("Nested"));
+    }
+}
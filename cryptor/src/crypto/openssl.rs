@@ -0,0 +1,126 @@
+use aes::Aes256;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, StreamCipher};
+use aes::cipher::block_padding::Pkcs7;
+use md5::{Digest, Md5};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use zeroize::Zeroizing;
+
+use crate::crypto::secret::SecretKey;
+
+// ---------------------------
+// `openssl enc` compatible format
+// Output format:  "Salted__" || salt (8) || ciphertext
+// ---------------------------
+// pbkdf2  = `openssl enc -pbkdf2` (PBKDF2-HMAC-SHA256, 10000 iterations)
+// sha256  = legacy EVP_BytesToKey with SHA-256 (OpenSSL >= 1.1.0 default)
+// md5     = legacy EVP_BytesToKey with MD5 (OpenSSL < 1.1.0 default)
+pub const KDFS: [&str; 3] = ["pbkdf2", "sha256", "md5"];
+
+const MAGIC: &[u8] = b"Salted__";
+const SALT_LEN: usize = 8;
+const KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+pub const PBKDF2_ITERS: u32 = 10_000;
+
+type Aes256CbcEnc = cbc::Encryptor<Aes256>;
+type Aes256CbcDec = cbc::Decryptor<Aes256>;
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+fn evp_bytes_to_key<D: Digest>(password: &str, salt: &[u8]) -> [u8; KEY_LEN + IV_LEN] {
+    let mut out = [0u8; KEY_LEN + IV_LEN];
+    let mut filled = 0;
+    let mut prev: Vec<u8> = Vec::new();
+
+    while filled < out.len() {
+        let mut hasher = D::new();
+        hasher.update(&prev);
+        hasher.update(password.as_bytes());
+        hasher.update(salt);
+        prev = hasher.finalize().to_vec();
+
+        let take = prev.len().min(out.len() - filled);
+        out[filled..filled + take].copy_from_slice(&prev[..take]);
+        filled += take;
+    }
+    out
+}
+
+fn derive_key_iv(kdf: &str, password: &str, salt: &[u8]) -> Result<(SecretKey<KEY_LEN>, [u8; IV_LEN])> {
+    let material = Zeroizing::new(match kdf {
+        "pbkdf2" => {
+            let mut out = [0u8; KEY_LEN + IV_LEN];
+            pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, PBKDF2_ITERS, &mut out);
+            out
+        }
+        "sha256" => evp_bytes_to_key::<Sha256>(password, salt),
+        "md5" => evp_bytes_to_key::<Md5>(password, salt),
+        _ => {
+            return Err(anyhow!(
+                "Invalid OpenSSL KDF '{}'. Allowed: {}",
+                kdf,
+                KDFS.join(", ")
+            ))
+        }
+    });
+
+    let mut key = SecretKey::<KEY_LEN>::zeroed();
+    let mut iv = [0u8; IV_LEN];
+    key.copy_from_slice(&material[..KEY_LEN]);
+    iv.copy_from_slice(&material[KEY_LEN..]);
+    Ok((key, iv))
+}
+
+pub fn encrypt(algo: &str, kdf: &str, password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let (key, iv) = derive_key_iv(kdf, password, &salt)?;
+
+    let ciphertext = match algo {
+        "aes-256-cbc" => Aes256CbcEnc::new(&(*key).into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(data),
+        "aes-256-ctr" => {
+            let mut buf = data.to_vec();
+            Aes256Ctr::new(&(*key).into(), &iv.into()).apply_keystream(&mut buf);
+            buf
+        }
+        _ => return Err(anyhow!("Invalid OpenSSL algorithm '{}'", algo)),
+    };
+
+    // "Salted__" || salt || ciphertext
+    let mut output = Vec::with_capacity(MAGIC.len() + SALT_LEN + ciphertext.len());
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&ciphertext);
+
+    Ok(output)
+}
+
+// Note: neither mode is authenticated. CBC rejects most wrong passwords through
+// the padding check, CTR cannot detect them at all.
+pub fn decrypt(algo: &str, kdf: &str, password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < MAGIC.len() + SALT_LEN || !data.starts_with(MAGIC) {
+        return Err(anyhow!("Not an OpenSSL salted file (missing 'Salted__' header)"));
+    }
+
+    let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let ciphertext = &data[MAGIC.len() + SALT_LEN..];
+
+    let (key, iv) = derive_key_iv(kdf, password, salt)?;
+
+    match algo {
+        "aes-256-cbc" => Aes256CbcDec::new(&(*key).into(), &iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map_err(|_| anyhow!("Invalid password or corrupted data")),
+        "aes-256-ctr" => {
+            let mut buf = ciphertext.to_vec();
+            Aes256Ctr::new(&(*key).into(), &iv.into()).apply_keystream(&mut buf);
+            Ok(buf)
+        }
+        _ => Err(anyhow!("Invalid OpenSSL algorithm '{}'", algo)),
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Result, anyhow};
+
+// ---------------------------
+// Length-hiding padding for aes / chacha, applied before sealing
+//
+// data || 0x80 || 0x00 ... (ISO/IEC 7816-4), up to the target length:
+//   padme    Padmé (PURBs, Nikitin et al. 2019): at most ~12% overhead, leaks O(log log L) bits
+//   block:N  next multiple of N bytes
+// The padding is encrypted with the data, so it is authenticated and its length stays hidden.
+// ---------------------------
+pub const SCHEMES: [&str; 3] = ["none", "padme", "block:N"];
+
+const MARKER: u8 = 0x80;
+
+const ID_PADME: u8 = 1;
+const ID_BLOCK: u8 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Padding {
+    #[default]
+    None,
+    Padme,
+    Block(u32),
+}
+
+// Rounds `len` up so that only its top O(log log len) bits can be non-zero
+fn padme(len: u64) -> u64 {
+    if len < 2 {
+        return len;
+    }
+    let e = 63 - len.leading_zeros() as u64;
+    let s = 64 - e.leading_zeros() as u64;
+    let mask = (1u64 << (e - s)) - 1;
+    (len + mask) & !mask
+}
+
+impl Padding {
+    pub fn parse(name: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid padding '{}'. Allowed: {}", name, SCHEMES.join(", "));
+        match name {
+            "none" => Ok(Padding::None),
+            "padme" => Ok(Padding::Padme),
+            _ => {
+                let size = name
+                    .strip_prefix("block:")
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or_else(invalid)?;
+                Ok(Padding::Block(size))
+            }
+        }
+    }
+
+    // Value stored in the header
+    pub fn encode(self) -> Vec<u8> {
+        match self {
+            Padding::None => Vec::new(),
+            Padding::Padme => vec![ID_PADME],
+            Padding::Block(size) => {
+                let mut out = vec![ID_BLOCK];
+                out.extend_from_slice(&size.to_be_bytes());
+                out
+            }
+        }
+    }
+
+    pub fn decode(value: &[u8]) -> Result<Self> {
+        match value {
+            [] => Ok(Padding::None),
+            [ID_PADME] => Ok(Padding::Padme),
+            [ID_BLOCK, a, b, c, d] if [*a, *b, *c, *d] != [0; 4] => Ok(Padding::Block(u32::from_be_bytes([*a, *b, *c, *d]))),
+            _ => Err(anyhow!("Unknown padding scheme in header")),
+        }
+    }
+
+    pub fn pad(self, mut data: Vec<u8>) -> Vec<u8> {
+        if self == Padding::None {
+            return data;
+        }
+
+        // The marker always takes one byte
+        let len = data.len() as u64 + 1;
+        let target = match self {
+            Padding::None => unreachable!(),
+            Padding::Padme => padme(len),
+            Padding::Block(size) => len.div_ceil(size as u64) * size as u64,
+        };
+
+        data.push(MARKER);
+        data.resize(target as usize, 0);
+        data
+    }
+
+    pub fn unpad(self, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        if self == Padding::None {
+            return Ok(data);
+        }
+
+        let end = data
+            .iter()
+            .rposition(|&b| b != 0)
+            .filter(|&i| data[i] == MARKER)
+            .ok_or_else(|| anyhow!("Corrupted data (invalid padding)"))?;
+        data.truncate(end);
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padme_boundaries() {
+        assert_eq!(padme(0), 0);
+        assert_eq!(padme(1), 1);
+        for e in 1..40 {
+            assert_eq!(padme(1 << e), 1 << e, "2^{}", e);
+        }
+        assert_eq!(padme(9), 10);
+        assert_eq!(padme(100), 104);
+        assert_eq!(padme(1000), 1024);
+        assert_eq!(padme(1025), 1088);
+
+        for len in (2..100_000).chain([u32::MAX as u64, 1 << 40 | 1]) {
+            let padded = padme(len);
+            assert!(padded >= len);
+            assert!((padded - len) as f64 <= 0.12 * len as f64, "{} -> {}", len, padded);
+        }
+    }
+
+    #[test]
+    fn block_sizes() {
+        // Always one marker byte, so an exact multiple grows by a whole block
+        assert_eq!(Padding::Block(1).pad(vec![7; 5]), [7, 7, 7, 7, 7, 0x80]);
+        assert_eq!(Padding::Block(16).pad(vec![7; 15]).len(), 16);
+        assert_eq!(Padding::Block(16).pad(vec![7; 16]).len(), 32);
+        assert_eq!(Padding::Block(16).pad(Vec::new()).len(), 16);
+        assert_eq!(Padding::Block(4096).pad(vec![7; 3 * 4096 - 1]).len(), 3 * 4096);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for padding in [Padding::None, Padding::Padme, Padding::Block(1), Padding::Block(7), Padding::Block(512)] {
+            for len in [0, 1, 2, 15, 16, 17, 127, 128, 129, 1000] {
+                // Trailing zeros and 0x80 in the data itself must survive
+                let mut data: Vec<u8> = (0..len).map(|i| (i % 3) as u8 * 0x40).collect();
+                data.extend_from_slice(&[0x80, 0]);
+                let padded = padding.pad(data.clone());
+                assert_eq!(padding.unpad(padded).unwrap(), data, "{:?} {}", padding, len);
+            }
+            assert_eq!(Padding::decode(&padding.encode()).unwrap(), padding);
+        }
+        assert_eq!(Padding::parse("block:64").unwrap(), Padding::Block(64));
+        assert!(Padding::parse("block:0").is_err());
+        assert!(Padding::parse("block:").is_err());
+    }
+
+    #[test]
+    fn unpad_rejects_bad_padding() {
+        for padded in [&[][..], &[0, 0, 0], &[1, 2, 3, 0], &[1, 0x80, 0x01, 0], &[1, 0x80, 0, 0xff]] {
+            assert!(Padding::Padme.unpad(padded.to_vec()).is_err(), "{:?}", padded);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_malformed_fields() {
+        for value in [&[ID_PADME, 0][..], &[ID_BLOCK], &[ID_BLOCK, 0, 0, 16], &[ID_BLOCK, 0, 0, 0, 0], &[ID_BLOCK, 0, 0, 0, 16, 0], &[9]] {
+            assert!(Padding::decode(value).is_err(), "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{Result, anyhow};
+use rand::rngs::OsRng;
+use rand::{Rng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::crypto::secret::SecretBytes;
+
+// ---------------------------
+// Random bytes, passwords and diceware passphrases, all drawn from OsRng
+// (the source used for salts, nonces and keys)
+//
+// Each character or word is picked uniformly, so a result holds
+// count × log2(choices) bits of entropy.
+//
+// data/eff_large_wordlist.txt is the EFF large wordlist as published (https://www.eff.org/dice):
+// 7776 "11111<TAB>abacus" lines, one per roll of five dice. data/fetch_wordlist.py downloads it.
+// ---------------------------
+pub const CHARSETS: [(&str, &str); 4] = [
+    ("lower", "abcdefghijklmnopqrstuvwxyz"),
+    ("upper", "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    ("digits", "0123456789"),
+    ("symbols", "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"),
+];
+
+const WORDLIST: &str = include_str!("../../data/eff_large_wordlist.txt");
+
+pub fn bytes(len: usize) -> SecretBytes {
+    let mut out = SecretBytes::from(vec![0u8; len]);
+    OsRng.fill_bytes(&mut out);
+    out
+}
+
+// `sets` is a comma-separated list of CHARSETS names; `extra` adds characters of its own.
+// Duplicates are removed so that every character is equally likely.
+pub fn alphabet(sets: &str, extra: Option<&str>) -> Result<Vec<char>> {
+    let mut chars = Vec::new();
+    for name in sets.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let (_, set) = CHARSETS.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+            let names: Vec<&str> = CHARSETS.iter().map(|(n, _)| *n).collect();
+            anyhow!("Invalid character set '{}'. Allowed: {}", name, names.join(", "))
+        })?;
+        chars.extend(set.chars());
+    }
+    chars.extend(extra.unwrap_or_default().chars().filter(|c| !c.is_control()));
+
+    chars.sort_unstable();
+    chars.dedup();
+    if chars.len() < 2 {
+        return Err(anyhow!("The password alphabet needs at least 2 distinct characters"));
+    }
+    Ok(chars)
+}
+
+pub fn password(alphabet: &[char], len: usize) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::with_capacity(len));
+    for _ in 0..len {
+        out.push(alphabet[OsRng.gen_range(0..alphabet.len())]);
+    }
+    out
+}
+
+pub fn wordlist() -> Vec<&'static str> {
+    WORDLIST
+        .lines()
+        .filter_map(|line| line.split('\t').next_back())
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+pub fn passphrase(words: &[&str], count: usize, separator: &str) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    for i in 0..count {
+        if i > 0 {
+            out.push_str(separator);
+        }
+        out.push_str(words[OsRng.gen_range(0..words.len())]);
+    }
+    out
+}
+
+pub fn entropy_bits(choices: usize, count: usize) -> f64 {
+    count as f64 * (choices as f64).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wordlist_has_unique_words() {
+        let mut words = wordlist();
+        let len = words.len();
+        assert_eq!(len, 7776);
+
+        // One line per roll of five dice, in order: 11111, 11112 ... 66666
+        let rolls: Vec<&str> = WORDLIST.lines().map(|line| line.split('\t').next().unwrap()).collect();
+        for (i, roll) in rolls.iter().enumerate() {
+            let expected: String = (0..5).rev().map(|d| char::from(b'1' + (i / 6usize.pow(d) % 6) as u8)).collect();
+            assert_eq!(*roll, expected, "line {}", i + 1);
+        }
+        assert!(words.iter().all(|w| w.chars().all(|c| c.is_ascii_lowercase() || c == '-')));
+        words.sort_unstable();
+        words.dedup();
+        assert_eq!(words.len(), len);
+    }
+
+    #[test]
+    fn password_uses_only_the_alphabet() {
+        let chars = alphabet("digits", Some("xx")).unwrap();
+        assert_eq!(chars.len(), 11);
+        let pwd = password(&chars, 200);
+        assert_eq!(pwd.chars().count(), 200);
+        assert!(pwd.chars().all(|c| chars.contains(&c)));
+        assert!(alphabet("digits,emoji", None).is_err());
+    }
+}
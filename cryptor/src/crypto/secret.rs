@@ -0,0 +1,133 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use zeroize::{Zeroize, Zeroizing};
+
+// ---------------------------
+// Secret holders: wiped on drop, redacted in Debug output
+//
+// SecretString  passwords and keys given on the command line
+// SecretKey     derived key material, kept on a heap page locked in RAM (mlock) when possible
+// SecretBytes   plaintext buffers
+//
+// Copies made before a value is wrapped (clap's own argument storage, the process
+// arguments, reallocations while a buffer grows) are out of our reach.
+// ---------------------------
+
+#[derive(Clone, Default)]
+pub struct SecretString(Zeroizing<String>);
+
+impl FromStr for SecretString {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(SecretString(Zeroizing::new(s.to_string())))
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SecretString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(<redacted>)")
+    }
+}
+
+pub struct SecretKey<const N: usize>(Box<[u8; N]>);
+
+impl<const N: usize> SecretKey<N> {
+    // All-zero key, to be filled in place (so no copy is left on the stack)
+    pub fn zeroed() -> Self {
+        let key = Box::new([0u8; N]);
+        // Best effort: fails silently when RLIMIT_MEMLOCK is exhausted (Unix only)
+        #[cfg(unix)]
+        // SAFETY: `key` is a live heap allocation of exactly N bytes, so the pointer is valid for
+        // N bytes; mlock only changes the paging of those pages and never reads or writes them.
+        unsafe {
+            libc::mlock(key.as_ptr().cast(), N);
+        }
+        SecretKey(key)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut key = Self::zeroed();
+        key.copy_from_slice(bytes);
+        Some(key)
+    }
+}
+
+impl<const N: usize> Deref for SecretKey<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> DerefMut for SecretKey<N> {
+    fn deref_mut(&mut self) -> &mut [u8; N] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Drop for SecretKey<N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        #[cfg(unix)]
+        // SAFETY: the box is still allocated here (it is freed after drop returns) and holds N bytes,
+        // the range that zeroed() locked; munlock on a range that was not locked is harmless.
+        unsafe {
+            libc::munlock(self.0.as_ptr().cast(), N);
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for SecretKey<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey<{}>(<redacted>)", N)
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretBytes(Zeroizing<Vec<u8>>);
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBytes(Zeroizing::new(bytes))
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes({} bytes, <redacted>)", self.0.len())
+    }
+}
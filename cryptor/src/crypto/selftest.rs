@@ -0,0 +1,285 @@
+use anyhow::{Result, anyhow};
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::crypto::{aes, base64, chacha, xor};
+
+// ---------------------------
+// Known-answer tests, run by `self-test` and by `cargo test`
+//
+//   AES-256-GCM          NIST GCM test cases 13-16 (McGrew & Viega, as used by SP 800-38D)
+//   ChaCha20-Poly1305    RFC 8439, section 2.8.2
+//   PBKDF2-HMAC-SHA1     RFC 6070
+//   PBKDF2-HMAC-SHA256   RFC 7914, section 11, also through aes::derive_key
+//   Base64               RFC 4648, section 10
+//   XOR                  fixed key of the xor mode
+//   aes / chacha files   a fixed file (PBKDF2 + AEAD) produced by an independent implementation
+// ---------------------------
+pub struct Check {
+    pub name: &'static str,
+    // Number of vectors that passed, or the first failure
+    pub result: Result<usize>,
+}
+
+type KnownAnswer = fn() -> Result<usize>;
+
+pub fn run() -> Vec<Check> {
+    let checks: [(&'static str, KnownAnswer); 8] = [
+        ("AES-256-GCM (NIST)", aes_gcm),
+        ("ChaCha20-Poly1305 (RFC 8439)", chacha20_poly1305),
+        ("PBKDF2-HMAC-SHA1 (RFC 6070)", pbkdf2_sha1),
+        ("PBKDF2-HMAC-SHA256 (RFC 7914)", pbkdf2_sha256),
+        ("Base64 (RFC 4648)", base64_rfc4648),
+        ("XOR", xor_mode),
+        ("aes file format", aes_file),
+        ("chacha file format", chacha_file),
+    ];
+    checks.into_iter().map(|(name, check)| Check { name, result: check() }).collect()
+}
+
+fn unhex(text: &str) -> Vec<u8> {
+    hex::decode(text).expect("test vector is valid hex")
+}
+
+fn key32(text: &str) -> [u8; 32] {
+    unhex(text).try_into().expect("test vector key is 32 bytes")
+}
+
+fn expect(what: &str, actual: &[u8], expected: &[u8]) -> Result<()> {
+    if actual != expected {
+        return Err(anyhow!("{}: got {}, expected {}", what, hex::encode(actual), hex::encode(expected)));
+    }
+    Ok(())
+}
+
+// ---------------------------
+// AEAD: seal must give the expected ciphertext || tag, open must give the plaintext back,
+// and open must reject the same ciphertext with one bit of the tag flipped
+// ---------------------------
+type Aead = (
+    fn(&[u8; 32], &[u8], &[u8], &[u8]) -> Result<Vec<u8>>,
+    fn(&[u8; 32], &[u8], &[u8], &[u8]) -> Result<Vec<u8>>,
+);
+
+fn aead_vector((seal, open): Aead, name: &str, key: &str, nonce: &str, aad: &str, plain: &[u8], sealed: &str) -> Result<()> {
+    let (key, nonce, aad, sealed) = (key32(key), unhex(nonce), unhex(aad), unhex(sealed));
+
+    expect(name, &seal(&key, &nonce, &aad, plain)?, &sealed)?;
+    expect(name, &open(&key, &nonce, &aad, &sealed)?, plain)?;
+
+    let mut forged = sealed.clone();
+    *forged.last_mut().unwrap() ^= 1;
+    if open(&key, &nonce, &aad, &forged).is_ok() {
+        return Err(anyhow!("{}: forged tag accepted", name));
+    }
+    Ok(())
+}
+
+// name, key, nonce, aad (hex), plaintext, ciphertext || tag (hex)
+type AeadVector<'a> = (&'a str, &'a str, &'a str, &'a str, &'a [u8], &'a str);
+
+const GCM_PLAIN: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                         1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+
+fn aes_gcm() -> Result<usize> {
+    let zero_key = "0000000000000000000000000000000000000000000000000000000000000000";
+    let key = "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308";
+    let plain = unhex(GCM_PLAIN);
+    let vectors: [AeadVector; 4] = [
+        ("test case 13", zero_key, "000000000000000000000000", "", &[], "530f8afbc74536b9a963b4f1c4cb738b"),
+        (
+            "test case 14",
+            zero_key,
+            "000000000000000000000000",
+            "",
+            &[0u8; 16],
+            "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919",
+        ),
+        (
+            "test case 15",
+            key,
+            "cafebabefacedbaddecaf888",
+            "",
+            &plain,
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838\
+             c5f61e6393ba7a0abcc9f662898015adb094dac5d93471bdec1a502270e3cc6c",
+        ),
+        (
+            "test case 16",
+            key,
+            "cafebabefacedbaddecaf888",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            &plain[..60],
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838\
+             c5f61e6393ba7a0abcc9f66276fc6ece0f4e1768cddf8853bb2d551b",
+        ),
+    ];
+
+    for (name, key, nonce, aad, plain, sealed) in vectors {
+        aead_vector((aes::seal, aes::open), name, key, nonce, aad, plain, sealed)?;
+    }
+    Ok(vectors.len())
+}
+
+fn chacha20_poly1305() -> Result<usize> {
+    aead_vector(
+        (chacha::seal, chacha::open),
+        "section 2.8.2",
+        "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f",
+        "070000004041424344454647",
+        "50515253c0c1c2c3c4c5c6c7",
+        b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.",
+        "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b\
+         1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+         3ff4def08e4b7a9de576d26586cec64b61161ae10b594f09e26a7e902ecbd0600691",
+    )?;
+    Ok(1)
+}
+
+// ---------------------------
+// PBKDF2
+// ---------------------------
+fn pbkdf2_sha1() -> Result<usize> {
+    let vectors: [(&[u8], &[u8], u32, &str); 5] = [
+        (b"password", b"salt", 1, "0c60c80f961f0e71f3a9b524af6012062fe037a6"),
+        (b"password", b"salt", 2, "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"),
+        (b"password", b"salt", 4096, "4b007901b765489abead49d926f721d065a429c1"),
+        (
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+        ),
+        (b"pass\0word", b"sa\0lt", 4096, "56fa6aa75548099dcc37d7f03425e0c3"),
+    ];
+
+    for (password, salt, rounds, expected) in vectors {
+        let expected = unhex(expected);
+        let mut out = vec![0u8; expected.len()];
+        pbkdf2_hmac::<Sha1>(password, salt, rounds, &mut out);
+        expect(&format!("{} rounds", rounds), &out, &expected)?;
+    }
+    Ok(vectors.len())
+}
+
+fn pbkdf2_sha256() -> Result<usize> {
+    let vectors: [(&str, &[u8], u32, &str); 2] = [
+        (
+            "passwd",
+            b"salt",
+            1,
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783",
+        ),
+        (
+            "Password",
+            b"NaCl",
+            80_000,
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+             a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d",
+        ),
+    ];
+
+    for (password, salt, rounds, expected) in vectors {
+        let mut out = [0u8; 64];
+        pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut out);
+        expect(&format!("{} rounds", rounds), &out, &unhex(expected))?;
+
+        // The key derivation the aes / chacha files use: the first 32 bytes of the same output
+        let key = aes::derive_key(password, salt, rounds);
+        expect(&format!("aes::derive_key, {} rounds", rounds), &*key, &unhex(expected)[..aes::KEY_LEN])?;
+    }
+    Ok(vectors.len())
+}
+
+// ---------------------------
+// Encodings and the xor mode
+// ---------------------------
+fn base64_rfc4648() -> Result<usize> {
+    let vectors = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    for (plain, encoded) in vectors {
+        expect(plain, base64::encode(plain.as_bytes()).as_bytes(), encoded.as_bytes())?;
+        expect(encoded, &base64::decode(encoded)?, plain.as_bytes())?;
+    }
+    Ok(vectors.len())
+}
+
+fn xor_mode() -> Result<usize> {
+    let plain = b"Known answer for the XOR mode!";
+    let expected = unhex("381b1f121c53040d01121119451f1c0750111a16453b3d3754060a1d1654");
+
+    expect("encrypt", &xor::xor_encrypt(xor::KEY, plain), &expected)?;
+    expect("decrypt", &xor::xor_decrypt(xor::KEY, &expected), plain)?;
+    Ok(1)
+}
+
+// ---------------------------
+// Whole files: salt 00..0f, nonce 10..1b, password "self-test", plaintext "cryptor self-test"
+// ---------------------------
+const FILE_PASSWORD: &str = "self-test";
+const FILE_PLAIN: &[u8] = b"cryptor self-test";
+
+fn aes_file() -> Result<usize> {
+    let file = unhex(
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b\
+         928e9c8adef09c1da859baf139205f927220720b9e7cb91c39095329fe0f7c6e47",
+    );
+    expect("decrypt", &aes::decrypt(FILE_PASSWORD, &file)?, FILE_PLAIN)?;
+    Ok(1)
+}
+
+fn chacha_file() -> Result<usize> {
+    let file = unhex(
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b\
+         a9b261056ce571281ad1e925084a9be047b39c3389b7a5613b28febeda76abeabb",
+    );
+    expect("decrypt", &chacha::decrypt(FILE_PASSWORD, &file)?, FILE_PLAIN)?;
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aes_gcm_vectors() {
+        aes_gcm().unwrap();
+    }
+
+    #[test]
+    fn chacha20_poly1305_vectors() {
+        chacha20_poly1305().unwrap();
+    }
+
+    #[test]
+    fn pbkdf2_vectors() {
+        pbkdf2_sha1().unwrap();
+        pbkdf2_sha256().unwrap();
+    }
+
+    #[test]
+    fn base64_vectors() {
+        base64_rfc4648().unwrap();
+    }
+
+    #[test]
+    fn xor_vector() {
+        xor_mode().unwrap();
+    }
+
+    #[test]
+    fn file_formats() {
+        aes_file().unwrap();
+        chacha_file().unwrap();
+    }
+}
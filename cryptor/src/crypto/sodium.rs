@@ -0,0 +1,277 @@
+use chacha20::{ChaCha20, hchacha};
+use chacha20::cipher::consts::U10;
+use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use poly1305::Poly1305;
+use poly1305::universal_hash::KeyInit;
+use salsa20::XSalsa20;
+use subtle::ConstantTimeEq;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::crypto::secret::SecretKey;
+use crate::crypto::verify::Verdict;
+
+// ---------------------------
+// libsodium-compatible modes, with raw 32-byte keys (hex on the command line)
+//
+// secretbox:     nonce (24) || crypto_secretbox_easy output (MAC (16) || ciphertext)
+// secretstream:  header (24) || chunks of crypto_secretstream_xchacha20poly1305_push,
+//                each CHUNK_LEN bytes of plaintext + 17, the last one tagged FINAL
+// ---------------------------
+pub const KEY_LEN: usize = 32;
+const XNONCE_LEN: usize = 24;
+const MAC_LEN: usize = 16;
+
+const HEADER_LEN: usize = 24;
+const ABYTES: usize = 1 + MAC_LEN;
+pub const CHUNK_LEN: usize = 4096;
+
+const TAG_MESSAGE: u8 = 0x00;
+const TAG_REKEY: u8 = 0x02;
+const TAG_FINAL: u8 = 0x03;
+
+pub fn parse_key(key: &str) -> Result<SecretKey<KEY_LEN>> {
+    hex::decode(key.trim())
+        .ok()
+        .map(Zeroizing::new)
+        .and_then(|k| SecretKey::from_slice(&k))
+        .ok_or_else(|| anyhow!("Invalid key (expected 32 bytes as hex)"))
+}
+
+pub fn generate_key() -> String {
+    let mut key = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    hex::encode(key)
+}
+
+// ---------------------------
+// crypto_secretbox (XSalsa20-Poly1305)
+// ---------------------------
+pub fn secretbox_encrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; XNONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    // The first 32 bytes of keystream are the Poly1305 key, the rest encrypts the message
+    let mut poly_key = Zeroizing::new([0u8; 32]);
+    let mut stream = XSalsa20::new(key.into(), &nonce.into());
+    stream.apply_keystream(&mut *poly_key);
+
+    let mut ciphertext = data.to_vec();
+    stream.apply_keystream(&mut ciphertext);
+
+    let mac = Poly1305::new((&*poly_key).into()).compute_unpadded(&ciphertext);
+
+    let mut output = Vec::with_capacity(XNONCE_LEN + MAC_LEN + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&mac);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+pub fn secretbox_decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < XNONCE_LEN + MAC_LEN {
+        return Err(anyhow!("Corrupted ciphertext"));
+    }
+
+    let nonce = &data[..XNONCE_LEN];
+    let mac = &data[XNONCE_LEN..XNONCE_LEN + MAC_LEN];
+    let ciphertext = &data[XNONCE_LEN + MAC_LEN..];
+
+    let mut poly_key = Zeroizing::new([0u8; 32]);
+    let mut stream = XSalsa20::new(key.into(), nonce.into());
+    stream.apply_keystream(&mut *poly_key);
+
+    let expected = Poly1305::new((&*poly_key).into()).compute_unpadded(ciphertext);
+    if !bool::from(expected.as_slice().ct_eq(mac)) {
+        return Err(anyhow!("Invalid key or corrupted data"));
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    stream.apply_keystream(&mut plaintext);
+    Ok(plaintext)
+}
+
+// ---------------------------
+// crypto_secretstream_xchacha20poly1305
+// ---------------------------
+struct StreamState {
+    key: SecretKey<KEY_LEN>,
+    // counter (4, little-endian) || inonce (8)
+    nonce: [u8; 12],
+}
+
+impl StreamState {
+    fn new(key: &[u8; KEY_LEN], header: &[u8]) -> Self {
+        let mut subkey = hchacha::<U10>(key.into(), header[..16].into());
+
+        let mut state = StreamState {
+            key: SecretKey::zeroed(),
+            nonce: [0u8; 12],
+        };
+        state.key.copy_from_slice(&subkey);
+        subkey.as_mut_slice().zeroize();
+        state.nonce[..4].copy_from_slice(&1u32.to_le_bytes());
+        state.nonce[4..].copy_from_slice(&header[16..24]);
+        state
+    }
+
+    fn cipher(&self, counter: u32) -> ChaCha20 {
+        let mut cipher = ChaCha20::new((&*self.key).into(), &self.nonce.into());
+        cipher.seek(counter as u64 * 64);
+        cipher
+    }
+
+    // Poly1305 over ad || pad || tag block || ciphertext || pad || lengths (ad is always empty here).
+    // libsodium pads the ciphertext with (0x10 - 64 + mlen) & 0xf zero bytes, i.e. mlen % 16.
+    fn mac(&self, block: &[u8; 64], ciphertext: &[u8]) -> [u8; MAC_LEN] {
+        let mut poly_key = Zeroizing::new([0u8; 32]);
+        self.cipher(0).apply_keystream(&mut *poly_key);
+
+        let mut input = Vec::with_capacity(64 + ciphertext.len() + 16 + 16);
+        input.extend_from_slice(block);
+        input.extend_from_slice(ciphertext);
+        input.resize(input.len() + ciphertext.len() % 16, 0);
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.extend_from_slice(&((64 + ciphertext.len()) as u64).to_le_bytes());
+
+        Poly1305::new((&*poly_key).into()).compute_unpadded(&input).into()
+    }
+
+    fn advance(&mut self, tag: u8, mac: &[u8; MAC_LEN]) {
+        for (n, m) in self.nonce[4..].iter_mut().zip(mac.iter()) {
+            *n ^= m;
+        }
+
+        let counter = u32::from_le_bytes([self.nonce[0], self.nonce[1], self.nonce[2], self.nonce[3]])
+            .wrapping_add(1);
+        self.nonce[..4].copy_from_slice(&counter.to_le_bytes());
+
+        if tag & TAG_REKEY != 0 || counter == 0 {
+            self.rekey();
+        }
+    }
+
+    fn rekey(&mut self) {
+        let mut material = Zeroizing::new([0u8; KEY_LEN + 8]);
+        material[..KEY_LEN].copy_from_slice(&self.key[..]);
+        material[KEY_LEN..].copy_from_slice(&self.nonce[4..]);
+
+        ChaCha20::new((&*self.key).into(), &self.nonce.into()).apply_keystream(&mut *material);
+
+        self.key.copy_from_slice(&material[..KEY_LEN]);
+        self.nonce[4..].copy_from_slice(&material[KEY_LEN..]);
+        self.nonce[..4].copy_from_slice(&1u32.to_le_bytes());
+    }
+
+    fn push(&mut self, message: &[u8], tag: u8, out: &mut Vec<u8>) {
+        let mut block = [0u8; 64];
+        block[0] = tag;
+        self.cipher(1).apply_keystream(&mut block);
+
+        let mut ciphertext = message.to_vec();
+        self.cipher(2).apply_keystream(&mut ciphertext);
+
+        let mac = self.mac(&block, &ciphertext);
+
+        out.push(block[0]);
+        out.extend_from_slice(&ciphertext);
+        out.extend_from_slice(&mac);
+
+        self.advance(tag, &mac);
+    }
+
+    fn pull(&mut self, chunk: &[u8]) -> Result<(Vec<u8>, u8)> {
+        if chunk.len() < ABYTES {
+            return Err(anyhow!("Truncated secretstream chunk"));
+        }
+        let ciphertext = &chunk[1..chunk.len() - MAC_LEN];
+        let mac = &chunk[chunk.len() - MAC_LEN..];
+
+        let mut block = [0u8; 64];
+        block[0] = chunk[0];
+        self.cipher(1).apply_keystream(&mut block);
+        let tag = block[0];
+        block[0] = chunk[0];
+
+        let expected = self.mac(&block, ciphertext);
+        if !bool::from(expected.ct_eq(mac)) {
+            return Err(anyhow!("Invalid key or corrupted data"));
+        }
+
+        let mut message = ciphertext.to_vec();
+        self.cipher(2).apply_keystream(&mut message);
+
+        self.advance(tag, &expected);
+        Ok((message, tag))
+    }
+}
+
+pub fn secretstream_encrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    let mut header = [0u8; HEADER_LEN];
+    OsRng.fill_bytes(&mut header);
+    let mut state = StreamState::new(key, &header);
+
+    let mut output = Vec::with_capacity(HEADER_LEN + data.len() + (data.len() / CHUNK_LEN + 1) * ABYTES);
+    output.extend_from_slice(&header);
+
+    let mut chunks = data.chunks(CHUNK_LEN).peekable();
+    if chunks.peek().is_none() {
+        state.push(&[], TAG_FINAL, &mut output);
+    }
+    while let Some(chunk) = chunks.next() {
+        let tag = if chunks.peek().is_none() { TAG_FINAL } else { TAG_MESSAGE };
+        state.push(chunk, tag, &mut output);
+    }
+
+    Ok(output)
+}
+
+// Pulls every chunk in order and hands each decrypted message to `sink`.
+// Shared by decrypt and verify, which only differ in what they do with the messages.
+fn secretstream_pull(key: &[u8; KEY_LEN], data: &[u8], mut sink: impl FnMut(Vec<u8>)) -> Verdict {
+    if data.len() < HEADER_LEN {
+        return Verdict::Truncated { offset: data.len() };
+    }
+    let mut state = StreamState::new(key, &data[..HEADER_LEN]);
+
+    let mut start = HEADER_LEN;
+    for (index, chunk) in data[HEADER_LEN..].chunks(CHUNK_LEN + ABYTES).enumerate() {
+        let end = start + chunk.len();
+        let tag = match state.pull(chunk) {
+            Ok((message, tag)) => {
+                sink(message);
+                tag
+            }
+            // Only the last chunk may be short: the file was cut inside it
+            Err(_) if chunk.len() < ABYTES => return Verdict::Truncated { offset: data.len() },
+            Err(_) => return Verdict::Tampered { start, end },
+        };
+
+        if tag == TAG_FINAL {
+            if end != data.len() {
+                return Verdict::Tampered { start: end, end: data.len() };
+            }
+            return Verdict::Valid { chunks: index + 1, bytes: data.len() };
+        }
+        start = end;
+    }
+
+    // No final chunk
+    Verdict::Truncated { offset: data.len() }
+}
+
+pub fn secretstream_decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(data.len());
+    match secretstream_pull(key, data, |message| output.extend_from_slice(&message)) {
+        Verdict::Valid { .. } => Ok(output),
+        Verdict::Truncated { .. } => Err(anyhow!("Truncated secretstream (no final chunk)")),
+        Verdict::Tampered { .. } => Err(anyhow!("Corrupted ciphertext")),
+    }
+}
+
+// Authenticates every chunk; each decrypted chunk is wiped and dropped at once
+pub fn secretstream_verify(key: &[u8; KEY_LEN], data: &[u8]) -> Verdict {
+    secretstream_pull(key, data, |message| drop(Zeroizing::new(message)))
+}
@@ -0,0 +1,156 @@
+use aes::Aes256;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use zeroize::Zeroizing;
+
+use crate::crypto::secret::SecretKey;
+
+// ---------------------------
+// Ansible Vault 1.1 / 1.2 (AES256)
+//
+// $ANSIBLE_VAULT;1.1;AES256            ($ANSIBLE_VAULT;1.2;AES256;<vault id>)
+// hex( hex(salt) \n hex(hmac) \n hex(ciphertext) ), 80 columns
+//
+// PBKDF2-HMAC-SHA256, 10000 iterations -> AES key (32) || HMAC key (32) || CTR IV (16)
+// ---------------------------
+const MAGIC: &str = "$ANSIBLE_VAULT";
+const CIPHER: &str = "AES256";
+pub const PBKDF2_ITERS: u32 = 10_000;
+const SALT_LEN: usize = 32;
+const KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+const BLOCK_LEN: usize = 16;
+const LINE_WIDTH: usize = 80;
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+struct Keys {
+    cipher: SecretKey<KEY_LEN>,
+    hmac: SecretKey<KEY_LEN>,
+    iv: [u8; IV_LEN],
+}
+
+fn derive_keys(password: &str, salt: &[u8]) -> Keys {
+    let mut material = Zeroizing::new([0u8; 2 * KEY_LEN + IV_LEN]);
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, PBKDF2_ITERS, &mut *material);
+
+    let mut keys = Keys {
+        cipher: SecretKey::zeroed(),
+        hmac: SecretKey::zeroed(),
+        iv: [0u8; IV_LEN],
+    };
+    keys.cipher.copy_from_slice(&material[..KEY_LEN]);
+    keys.hmac.copy_from_slice(&material[KEY_LEN..2 * KEY_LEN]);
+    keys.iv.copy_from_slice(&material[2 * KEY_LEN..]);
+    keys
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|_| anyhow!("Invalid HMAC key"))?;
+    mac.update(data);
+    Ok(mac)
+}
+
+// Returns (vault id, hex payload) from the envelope
+pub fn parse_envelope(data: &[u8]) -> Result<(Option<String>, String)> {
+    let text = std::str::from_utf8(data).map_err(|_| anyhow!("Invalid Ansible Vault data"))?;
+    let mut lines = text.lines();
+
+    let header: Vec<&str> = lines
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .split(';')
+        .collect();
+    if header.first() != Some(&MAGIC) {
+        return Err(anyhow!("Not an Ansible Vault file (missing $ANSIBLE_VAULT header)"));
+    }
+
+    let vault_id = match (header.get(1), header.get(2)) {
+        (Some(&"1.1"), Some(&CIPHER)) => None,
+        (Some(&"1.2"), Some(&CIPHER)) => header.get(3).map(|id| id.to_string()),
+        _ => return Err(anyhow!("Unsupported Ansible Vault header '{}'", header.join(";"))),
+    };
+
+    let payload = lines.flat_map(|l| l.split_whitespace()).collect();
+    Ok((vault_id, payload))
+}
+
+pub fn vault_id(data: &[u8]) -> Option<String> {
+    parse_envelope(data).ok().and_then(|(id, _)| id)
+}
+
+pub fn encrypt(password: &str, vault_id: Option<&str>, data: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let keys = derive_keys(password, &salt);
+
+    // PKCS#7 padding, then AES-256-CTR
+    let pad = BLOCK_LEN - data.len() % BLOCK_LEN;
+    let mut ciphertext = data.to_vec();
+    ciphertext.extend(std::iter::repeat_n(pad as u8, pad));
+    Aes256Ctr::new(&(*keys.cipher).into(), &keys.iv.into()).apply_keystream(&mut ciphertext);
+
+    let tag = hmac(&keys.hmac[..], &ciphertext)?.finalize().into_bytes();
+
+    let inner = format!("{}\n{}\n{}", hex::encode(salt), hex::encode(tag), hex::encode(&ciphertext));
+    let outer = hex::encode(inner);
+
+    let mut out = match vault_id {
+        Some(id) if id != "default" => format!("{};1.2;{};{}\n", MAGIC, CIPHER, id),
+        _ => format!("{};1.1;{}\n", MAGIC, CIPHER),
+    };
+    for line in outer.as_bytes().chunks(LINE_WIDTH) {
+        out.push_str(&String::from_utf8_lossy(line));
+        out.push('\n');
+    }
+
+    Ok(out.into_bytes())
+}
+
+pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let (_, payload) = parse_envelope(data)?;
+
+    let inner = hex::decode(payload).map_err(|_| anyhow!("Corrupted Ansible Vault payload"))?;
+    let mut parts = inner.split(|&b| b == b'\n');
+    let (salt, tag, ciphertext) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(s), Some(t), Some(c), None) => (
+            hex::decode(s).map_err(|_| anyhow!("Corrupted Ansible Vault payload"))?,
+            hex::decode(t).map_err(|_| anyhow!("Corrupted Ansible Vault payload"))?,
+            hex::decode(c).map_err(|_| anyhow!("Corrupted Ansible Vault payload"))?,
+        ),
+        _ => return Err(anyhow!("Corrupted Ansible Vault payload")),
+    };
+
+    let keys = derive_keys(password, &salt);
+
+    // verify_slice compares in constant time
+    hmac(&keys.hmac[..], &ciphertext)?
+        .verify_slice(&tag)
+        .map_err(|_| anyhow!("Invalid password or corrupted data"))?;
+
+    let mut plaintext = ciphertext;
+    Aes256Ctr::new(&(*keys.cipher).into(), &keys.iv.into()).apply_keystream(&mut plaintext);
+
+    let pad = *plaintext.last().ok_or_else(|| anyhow!("Corrupted Ansible Vault payload"))? as usize;
+    if pad == 0 || pad > BLOCK_LEN || pad > plaintext.len()
+        || plaintext[plaintext.len() - pad..].iter().any(|&b| b as usize != pad)
+    {
+        return Err(anyhow!("Corrupted Ansible Vault payload (bad padding)"));
+    }
+    plaintext.truncate(plaintext.len() - pad);
+
+    Ok(plaintext)
+}
+
+// Re-encrypts under a new password, keeping the vault id
+pub fn rekey(old_password: &str, new_password: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let plaintext = decrypt(old_password, data)?;
+    encrypt(new_password, vault_id(data).as_deref(), &plaintext)
+}
@@ -1,3 +1,6 @@
+// Fixed key of the xor mode (obfuscation only, not encryption)
+pub const KEY: &[u8] = b"supersecretkey";
+
 pub fn xor_encrypt(key: &[u8], data: &[u8]) -> Vec<u8> {
     data.iter()
         .enumerate()
@@ -0,0 +1,549 @@
+use aes::{Aes128, Aes192, Aes256};
+use aes::cipher::{KeyIvInit, StreamCipher};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use anyhow::{Result, anyhow};
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+use crate::crypto::secret::SecretBytes;
+
+// ---------------------------
+// Password-protected ZIP archives, WinZip AE-2 (https://www.winzip.com/en/support/aes-encryption/)
+//
+// Each file:  local header (method 99 + 0x9901 extra field) || salt || password check (2)
+//             || AES-CTR ciphertext of the (deflated) data || HMAC-SHA1 (10)
+// PBKDF2-HMAC-SHA1, 1000 iterations -> AES key || HMAC key || password check
+// AES-256 is written; AES-128 / 192 and AE-1 are also read.
+// Directories are stored as empty, unencrypted entries.
+// ---------------------------
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x0605_4b50;
+const LOCAL_HEADER_LEN: usize = 30;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIR_LEN: usize = 22;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+const METHOD_AES: u16 = 99;
+
+const FLAG_ENCRYPTED: u16 = 0x0001;
+const FLAG_UTF8: u16 = 0x0800;
+
+// 5.1 is the first version with AES, 3 << 8 is "made on Unix"
+const VERSION_NEEDED: u16 = 51;
+const VERSION_MADE_BY: u16 = (3 << 8) | 63;
+
+const AES_EXTRA_ID: u16 = 0x9901;
+const AES_VENDOR: &[u8; 2] = b"AE";
+const AE_1: u16 = 1;
+const AE_2: u16 = 2;
+const AES_STRENGTH_256: u8 = 3;
+
+pub const PBKDF2_ITERS: u32 = 1000;
+const PWV_LEN: usize = 2;
+const AUTH_LEN: usize = 10;
+
+const FILE_MODE: u32 = 0o100644;
+const DIR_MODE: u32 = 0o040755;
+const MSDOS_DIR_ATTR: u32 = 0x10;
+
+pub struct Entry {
+    // Relative path, "/"-separated; directories end with "/"
+    pub name: String,
+    pub data: SecretBytes,
+    // Unix time, stored as a DOS date (UTC, 2-second resolution)
+    pub modified: u64,
+}
+
+impl Entry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+// Entry as seen without the password (inspect)
+pub struct Listing {
+    pub name: String,
+    // "AES-256 (AE-2)", "ZipCrypto" or "none"
+    pub encryption: String,
+    // Actual compression method (stored, deflate…)
+    pub method: u16,
+    pub salt: Option<Vec<u8>>,
+    pub compressed: u32,
+    pub size: u32,
+}
+
+// ---------------------------
+// AES: key sizes by WinZip "strength" (1, 2, 3)
+// ---------------------------
+fn key_len(strength: u8) -> Result<usize> {
+    match strength {
+        1 => Ok(16),
+        2 => Ok(24),
+        3 => Ok(32),
+        _ => Err(anyhow!("Unsupported WinZip AES strength {}", strength)),
+    }
+}
+
+// Salt is half the key length
+fn salt_len(strength: u8) -> Result<usize> {
+    Ok(key_len(strength)? / 2)
+}
+
+struct Keys {
+    cipher: Zeroizing<Vec<u8>>,
+    hmac: Zeroizing<Vec<u8>>,
+    check: [u8; PWV_LEN],
+}
+
+fn derive_keys(password: &str, salt: &[u8], strength: u8) -> Result<Keys> {
+    let len = key_len(strength)?;
+    let mut material = Zeroizing::new(vec![0u8; 2 * len + PWV_LEN]);
+    pbkdf2_hmac::<Sha1>(password.as_bytes(), salt, PBKDF2_ITERS, &mut material);
+
+    Ok(Keys {
+        cipher: Zeroizing::new(material[..len].to_vec()),
+        hmac: Zeroizing::new(material[len..2 * len].to_vec()),
+        check: [material[2 * len], material[2 * len + 1]],
+    })
+}
+
+// AES-CTR with a little-endian counter starting at 1
+fn apply_ctr(key: &[u8], data: &mut [u8]) -> Result<()> {
+    let mut iv = [0u8; 16];
+    iv[0] = 1;
+
+    match key.len() {
+        16 => ctr::Ctr128LE::<Aes128>::new(key.into(), &iv.into()).apply_keystream(data),
+        24 => ctr::Ctr128LE::<Aes192>::new(key.into(), &iv.into()).apply_keystream(data),
+        32 => ctr::Ctr128LE::<Aes256>::new(key.into(), &iv.into()).apply_keystream(data),
+        _ => return Err(anyhow!("Invalid AES key length")),
+    }
+    Ok(())
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Result<Hmac<Sha1>> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key)
+        .map_err(|_| anyhow!("Invalid HMAC key"))?;
+    mac.update(data);
+    Ok(mac)
+}
+
+// ---------------------------
+// DOS date / time
+// ---------------------------
+fn dos_datetime(unix: u64) -> (u16, u16) {
+    let days = (unix / 86_400) as i64;
+    let secs = unix % 86_400;
+
+    // Days since 1970-01-01 -> civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    // DOS dates start in 1980
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let year = year.min(2107);
+
+    let time = ((secs / 3600) << 11) | (((secs % 3600) / 60) << 5) | ((secs % 60) / 2);
+    let date = ((year - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+fn unix_time(time: u16, date: u16) -> u64 {
+    let year = 1980 + (date >> 9) as i64;
+    let month = ((date >> 5) & 0x0F).clamp(1, 12) as i64;
+    let day = (date & 0x1F).max(1) as i64;
+
+    // Civil date -> days since 1970-01-01
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = (time >> 11) as i64 * 3600 + ((time >> 5) & 0x3F) as i64 * 60 + (time & 0x1F) as i64 * 2;
+    (days * 86_400 + secs).max(0) as u64
+}
+
+// ---------------------------
+// Writing
+// ---------------------------
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn to_u32(value: usize) -> Result<u32> {
+    u32::try_from(value).map_err(|_| anyhow!("Archive too large (ZIP64 is not supported)"))
+}
+
+fn aes_extra(method: u16) -> Vec<u8> {
+    let mut extra = Vec::with_capacity(11);
+    put_u16(&mut extra, AES_EXTRA_ID);
+    put_u16(&mut extra, 7);
+    put_u16(&mut extra, AE_2);
+    extra.extend_from_slice(AES_VENDOR);
+    extra.push(AES_STRENGTH_256);
+    put_u16(&mut extra, method);
+    extra
+}
+
+// Deflates the data, unless that does not make it smaller
+fn compress(data: &[u8]) -> Result<(u16, Zeroizing<Vec<u8>>)> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    let deflated = Zeroizing::new(encoder.finish()?);
+
+    if deflated.len() < data.len() {
+        Ok((METHOD_DEFLATE, deflated))
+    } else {
+        Ok((METHOD_STORED, Zeroizing::new(data.to_vec())))
+    }
+}
+
+fn encrypt_entry(password: &str, data: &[u8]) -> Result<(u16, Vec<u8>)> {
+    let (method, mut payload) = compress(data)?;
+
+    let mut salt = vec![0u8; salt_len(AES_STRENGTH_256)?];
+    OsRng.fill_bytes(&mut salt);
+    let keys = derive_keys(password, &salt, AES_STRENGTH_256)?;
+
+    apply_ctr(&keys.cipher, &mut payload)?;
+    let tag = hmac(&keys.hmac, &payload)?.finalize().into_bytes();
+
+    let mut out = Vec::with_capacity(salt.len() + PWV_LEN + payload.len() + AUTH_LEN);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&keys.check);
+    out.extend_from_slice(&payload);
+    out.extend_from_slice(&tag[..AUTH_LEN]);
+    Ok((method, out))
+}
+
+pub fn encrypt(password: &str, entries: &[Entry]) -> Result<Vec<u8>> {
+    if entries.len() > u16::MAX as usize {
+        return Err(anyhow!("Too many files (ZIP64 is not supported)"));
+    }
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+
+    for entry in entries {
+        let offset = to_u32(out.len())?;
+        let (time, date) = dos_datetime(entry.modified);
+        let name = entry.name.as_bytes();
+
+        // AE-2 leaves the CRC at zero: the HMAC authenticates the data instead
+        let (flags, method, extra, body, size, attrs) = if entry.is_dir() {
+            (FLAG_UTF8, METHOD_STORED, Vec::new(), Vec::new(), 0, (DIR_MODE << 16) | MSDOS_DIR_ATTR)
+        } else {
+            let (method, body) = encrypt_entry(password, &entry.data)?;
+            (FLAG_UTF8 | FLAG_ENCRYPTED, METHOD_AES, aes_extra(method), body, entry.data.len(), FILE_MODE << 16)
+        };
+
+        put_u32(&mut out, LOCAL_HEADER_SIG);
+        put_u16(&mut out, VERSION_NEEDED);
+        put_u16(&mut out, flags);
+        put_u16(&mut out, method);
+        put_u16(&mut out, time);
+        put_u16(&mut out, date);
+        put_u32(&mut out, 0);
+        put_u32(&mut out, to_u32(body.len())?);
+        put_u32(&mut out, to_u32(size)?);
+        put_u16(&mut out, name.len() as u16);
+        put_u16(&mut out, extra.len() as u16);
+        out.extend_from_slice(name);
+        out.extend_from_slice(&extra);
+        out.extend_from_slice(&body);
+
+        put_u32(&mut central, CENTRAL_HEADER_SIG);
+        put_u16(&mut central, VERSION_MADE_BY);
+        put_u16(&mut central, VERSION_NEEDED);
+        put_u16(&mut central, flags);
+        put_u16(&mut central, method);
+        put_u16(&mut central, time);
+        put_u16(&mut central, date);
+        put_u32(&mut central, 0);
+        put_u32(&mut central, to_u32(body.len())?);
+        put_u32(&mut central, to_u32(size)?);
+        put_u16(&mut central, name.len() as u16);
+        put_u16(&mut central, extra.len() as u16);
+        put_u16(&mut central, 0); // comment
+        put_u16(&mut central, 0); // disk
+        put_u16(&mut central, 0); // internal attributes
+        put_u32(&mut central, attrs);
+        put_u32(&mut central, offset);
+        central.extend_from_slice(name);
+        central.extend_from_slice(&extra);
+    }
+
+    let central_offset = to_u32(out.len())?;
+    let central_len = to_u32(central.len())?;
+    out.extend_from_slice(&central);
+
+    put_u32(&mut out, END_OF_CENTRAL_DIR_SIG);
+    put_u16(&mut out, 0);
+    put_u16(&mut out, 0);
+    put_u16(&mut out, entries.len() as u16);
+    put_u16(&mut out, entries.len() as u16);
+    put_u32(&mut out, central_len);
+    put_u32(&mut out, central_offset);
+    put_u16(&mut out, 0);
+
+    Ok(out)
+}
+
+// ---------------------------
+// Reading
+// ---------------------------
+fn take(data: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    pos.checked_add(len)
+        .and_then(|end| data.get(pos..end))
+        .ok_or_else(|| anyhow!("Truncated or corrupted ZIP archive"))
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16> {
+    let b = take(data, pos, 2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32> {
+    let b = take(data, pos, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn find_end_of_central_dir(data: &[u8]) -> Result<usize> {
+    // The record is followed by a comment of at most 65535 bytes
+    let last = data
+        .len()
+        .checked_sub(END_OF_CENTRAL_DIR_LEN)
+        .ok_or_else(|| anyhow!("Not a ZIP archive"))?;
+    let first = last.saturating_sub(u16::MAX as usize);
+
+    (first..=last)
+        .rev()
+        .find(|&pos| u32_at(data, pos).ok() == Some(END_OF_CENTRAL_DIR_SIG))
+        .ok_or_else(|| anyhow!("Not a ZIP archive (no end of central directory)"))
+}
+
+// Returns (AE version, strength, actual compression method) from the 0x9901 extra field
+fn parse_aes_extra(extra: &[u8]) -> Result<(u16, u8, u16)> {
+    let mut pos = 0;
+    while pos + 4 <= extra.len() {
+        let id = u16_at(extra, pos)?;
+        let len = u16_at(extra, pos + 2)? as usize;
+        let body = take(extra, pos + 4, len)?;
+
+        if id == AES_EXTRA_ID {
+            if len < 7 || &body[2..4] != AES_VENDOR {
+                return Err(anyhow!("Invalid WinZip AES extra field"));
+            }
+            return Ok((u16_at(body, 0)?, body[4], u16_at(body, 5)?));
+        }
+        pos += 4 + len;
+    }
+    Err(anyhow!("Missing WinZip AES extra field"))
+}
+
+// Rejects absolute paths and ".." so that extraction stays inside the output directory
+fn check_name(name: &str) -> Result<()> {
+    let unsafe_name = name.is_empty()
+        || name.starts_with('/')
+        || name.contains('\\')
+        || name.contains(':')
+        || name.split('/').any(|part| part == "..");
+    if unsafe_name {
+        return Err(anyhow!("Unsafe path in ZIP archive: {}", name));
+    }
+    Ok(())
+}
+
+fn decompress(method: u16, data: &[u8], size: usize) -> Result<SecretBytes> {
+    match method {
+        METHOD_STORED => Ok(SecretBytes::from(data.to_vec())),
+        METHOD_DEFLATE => {
+            // `size` comes from the unauthenticated central directory: only trust it up to a
+            // small multiple of the data, the caller checks the final length anyway
+            let mut out = SecretBytes::from(Vec::with_capacity(size.min(data.len().saturating_mul(4))));
+            DeflateDecoder::new(data)
+                .take(size as u64 + 1)
+                .read_to_end(&mut out)
+                .map_err(|_| anyhow!("Corrupted ZIP entry (bad deflate stream)"))?;
+            Ok(out)
+        }
+        _ => Err(anyhow!("Unsupported ZIP compression method {}", method)),
+    }
+}
+
+fn decrypt_entry(password: &str, extra: &[u8], body: &[u8]) -> Result<(u16, u16, Zeroizing<Vec<u8>>)> {
+    let (version, strength, method) = parse_aes_extra(extra)?;
+    if version != AE_1 && version != AE_2 {
+        return Err(anyhow!("Unsupported WinZip AES version {}", version));
+    }
+
+    let salt_len = salt_len(strength)?;
+    if body.len() < salt_len + PWV_LEN + AUTH_LEN {
+        return Err(anyhow!("Truncated or corrupted ZIP archive"));
+    }
+    let salt = &body[..salt_len];
+    let check = &body[salt_len..salt_len + PWV_LEN];
+    let payload = &body[salt_len + PWV_LEN..body.len() - AUTH_LEN];
+    let tag = &body[body.len() - AUTH_LEN..];
+
+    let keys = derive_keys(password, salt, strength)?;
+    if check != keys.check {
+        return Err(anyhow!("Invalid password"));
+    }
+
+    // verify_truncated_left compares in constant time
+    hmac(&keys.hmac, payload)?
+        .verify_truncated_left(tag)
+        .map_err(|_| anyhow!("Invalid password or corrupted data"))?;
+
+    let mut plain = Zeroizing::new(payload.to_vec());
+    apply_ctr(&keys.cipher, &mut plain)?;
+    Ok((version, method, plain))
+}
+
+pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<Entry>> {
+    let end = find_end_of_central_dir(data)?;
+    let count = u16_at(data, end + 10)? as usize;
+    let central_offset = u32_at(data, end + 16)?;
+    if count == u16::MAX as usize || central_offset == u32::MAX {
+        return Err(anyhow!("ZIP64 archives are not supported"));
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    let mut pos = central_offset as usize;
+
+    for _ in 0..count {
+        if u32_at(data, pos)? != CENTRAL_HEADER_SIG {
+            return Err(anyhow!("Corrupted ZIP central directory"));
+        }
+        let flags = u16_at(data, pos + 8)?;
+        let method = u16_at(data, pos + 10)?;
+        let time = u16_at(data, pos + 12)?;
+        let date = u16_at(data, pos + 14)?;
+        let crc = u32_at(data, pos + 16)?;
+        let compressed = u32_at(data, pos + 20)?;
+        let size = u32_at(data, pos + 24)?;
+        let name_len = u16_at(data, pos + 28)? as usize;
+        let extra_len = u16_at(data, pos + 30)? as usize;
+        let comment_len = u16_at(data, pos + 32)? as usize;
+        let local_offset = u32_at(data, pos + 42)? as usize;
+        let name = String::from_utf8_lossy(take(data, pos + CENTRAL_HEADER_LEN, name_len)?).into_owned();
+        let extra = take(data, pos + CENTRAL_HEADER_LEN + name_len, extra_len)?;
+        pos += CENTRAL_HEADER_LEN + name_len + extra_len + comment_len;
+
+        if compressed == u32::MAX || size == u32::MAX {
+            return Err(anyhow!("ZIP64 archives are not supported"));
+        }
+        check_name(&name)?;
+
+        if u32_at(data, local_offset)? != LOCAL_HEADER_SIG {
+            return Err(anyhow!("Corrupted ZIP local header for {}", name));
+        }
+        let start = local_offset
+            + LOCAL_HEADER_LEN
+            + u16_at(data, local_offset + 26)? as usize
+            + u16_at(data, local_offset + 28)? as usize;
+        let body = take(data, start, compressed as usize)?;
+
+        let (check_crc, method, payload) = if method == METHOD_AES {
+            let (version, method, payload) = decrypt_entry(password, extra, body)
+                .map_err(|e| anyhow!("{}: {}", name, e))?;
+            (version == AE_1, method, payload)
+        } else if flags & FLAG_ENCRYPTED != 0 {
+            return Err(anyhow!("{}: traditional ZipCrypto encryption is not supported", name));
+        } else if name.ends_with('/') && compressed == 0 && size == 0 {
+            // Directories carry no data, so there is nothing to encrypt
+            (false, METHOD_STORED, Zeroizing::new(Vec::new()))
+        } else {
+            // Anyone could add or replace such an entry without the password
+            return Err(anyhow!("{}: entry is not encrypted (only WinZip AES entries are accepted)", name));
+        };
+
+        let plain = decompress(method, &payload, size as usize)?;
+        if plain.len() != size as usize {
+            return Err(anyhow!("{}: size mismatch", name));
+        }
+        if check_crc {
+            let mut hasher = flate2::Crc::new();
+            hasher.update(&plain);
+            if hasher.sum() != crc {
+                return Err(anyhow!("{}: CRC mismatch", name));
+            }
+        }
+
+        entries.push(Entry { name, data: plain, modified: unix_time(time, date) });
+    }
+
+    Ok(entries)
+}
+
+// Lists the entries and their encryption parameters; nothing is decrypted
+pub fn list(data: &[u8]) -> Result<Vec<Listing>> {
+    let end = find_end_of_central_dir(data)?;
+    let count = u16_at(data, end + 10)? as usize;
+    let mut pos = u32_at(data, end + 16)? as usize;
+
+    let mut listing = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(data, pos)? != CENTRAL_HEADER_SIG {
+            return Err(anyhow!("Corrupted ZIP central directory"));
+        }
+        let flags = u16_at(data, pos + 8)?;
+        let mut method = u16_at(data, pos + 10)?;
+        let compressed = u32_at(data, pos + 20)?;
+        let size = u32_at(data, pos + 24)?;
+        let name_len = u16_at(data, pos + 28)? as usize;
+        let extra_len = u16_at(data, pos + 30)? as usize;
+        let comment_len = u16_at(data, pos + 32)? as usize;
+        let local_offset = u32_at(data, pos + 42)? as usize;
+        let name = String::from_utf8_lossy(take(data, pos + CENTRAL_HEADER_LEN, name_len)?).into_owned();
+        let extra = take(data, pos + CENTRAL_HEADER_LEN + name_len, extra_len)?;
+        pos += CENTRAL_HEADER_LEN + name_len + extra_len + comment_len;
+
+        let (encryption, salt) = if method == METHOD_AES {
+            let (version, strength, actual) = parse_aes_extra(extra)?;
+            method = actual;
+
+            // The salt starts the entry data, after the local header
+            let start = local_offset
+                + LOCAL_HEADER_LEN
+                + u16_at(data, local_offset + 26)? as usize
+                + u16_at(data, local_offset + 28)? as usize;
+            let salt = take(data, start, salt_len(strength)?)?.to_vec();
+            (format!("AES-{} (AE-{})", key_len(strength)? * 8, version), Some(salt))
+        } else if flags & FLAG_ENCRYPTED != 0 {
+            ("ZipCrypto".to_string(), None)
+        } else {
+            ("none".to_string(), None)
+        };
+
+        listing.push(Listing { name, encryption, method, salt, compressed, size });
+    }
+
+    Ok(listing)
+}
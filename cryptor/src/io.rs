@@ -1,9 +1,346 @@
-use std::fs;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self as stdio, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+#[cfg(unix)]
+use std::fs::Permissions;
+#[cfg(unix)]
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tempfile::NamedTempFile;
+use rand::rngs::OsRng;
+use rand::RngCore;
 
-pub fn read_file(path: &str) -> anyhow::Result<Vec<u8>> {
+use crate::crypto::metadata::{self, Metadata};
+
+// Regular outputs get 0666 minus the umask; decrypted outputs are owner-only.
+// Outside Unix, modes are not applied: files get the platform's default permissions.
+const DEFAULT_MODE: u32 = 0o666;
+const PRIVATE_MODE: u32 = 0o600;
+
+const SHRED_PASSES: usize = 3;
+const SHRED_CHUNK: usize = 64 * 1024;
+
+pub fn read_file(path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
     Ok(fs::read(path)?)
 }
 
-pub fn write_file(path: &str, data: &[u8]) -> anyhow::Result<()> {
-    Ok(fs::write(path, data)?)
-}
\ No newline at end of file
+pub fn write_file(path: impl AsRef<Path>, data: &[u8], force: bool) -> anyhow::Result<()> {
+    let mut writer = AtomicWriter::create(path.as_ref(), force, DEFAULT_MODE)?;
+    writer.write_all(data)?;
+    writer.commit()
+}
+
+// Same as write_file, but the data is read back from disk and compared before the rename
+pub fn write_file_verified(path: impl AsRef<Path>, data: &[u8], force: bool) -> anyhow::Result<()> {
+    let mut writer = AtomicWriter::create(path.as_ref(), force, DEFAULT_MODE)?;
+    writer.write_all(data)?;
+    writer.flush()?;
+
+    if fs::read(writer.file.get_ref().path())? != data {
+        anyhow::bail!("Verification failed: {} was not written correctly", path.as_ref().display());
+    }
+    writer.commit()
+}
+
+// Same as write_file, readable by the owner only (for decrypted data)
+pub fn write_private(path: impl AsRef<Path>, data: &[u8], force: bool) -> anyhow::Result<()> {
+    let mut writer = AtomicWriter::create(path.as_ref(), force, PRIVATE_MODE)?;
+    writer.write_all(data)?;
+    writer.commit()
+}
+
+// Streaming access, for inputs too large to hold in memory
+pub fn open_reader(path: &str) -> anyhow::Result<BufReader<File>> {
+    Ok(BufReader::new(File::open(path)?))
+}
+
+pub fn create_writer(path: &str, force: bool) -> anyhow::Result<AtomicWriter> {
+    AtomicWriter::create(Path::new(path), force, DEFAULT_MODE)
+}
+
+pub fn create_private_writer(path: &str, force: bool) -> anyhow::Result<AtomicWriter> {
+    AtomicWriter::create(Path::new(path), force, PRIVATE_MODE)
+}
+
+// ---------------------------
+// Atomic output
+//
+// Data goes to a temporary file in the destination directory; commit() fsyncs it and
+// renames it over the destination. Dropped without commit (after an error), the
+// temporary file is removed, so a failed run never leaves a partial output behind.
+// ---------------------------
+pub struct AtomicWriter {
+    file: BufWriter<NamedTempFile>,
+    path: PathBuf,
+    force: bool,
+}
+
+impl AtomicWriter {
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn create(path: &Path, force: bool, mode: u32) -> anyhow::Result<Self> {
+        if !force && fs::symlink_metadata(path).is_ok() {
+            anyhow::bail!("Output file already exists: {} (use --force to overwrite)", path.display());
+        }
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(".cryptor-");
+        #[cfg(unix)]
+        builder.permissions(Permissions::from_mode(mode));
+        let file = builder.tempfile_in(dir)?;
+
+        Ok(AtomicWriter { file: BufWriter::new(file), path: path.to_path_buf(), force })
+    }
+
+    pub fn commit(self) -> anyhow::Result<()> {
+        let file = self.file.into_inner().map_err(|e| e.into_error())?;
+        file.as_file().sync_all()?;
+
+        if self.force {
+            file.persist(&self.path)?;
+        } else {
+            // Fails instead of replacing a file created in the meantime
+            file.persist_noclobber(&self.path).map_err(|e| {
+                anyhow::anyhow!("Cannot write {}: {}", self.path.display(), e.error)
+            })?;
+        }
+
+        // Make the rename itself durable
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty())
+            && let Ok(dir) = File::open(dir)
+        {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+}
+
+impl Write for AtomicWriter {
+    fn write(&mut self, buf: &[u8]) -> stdio::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> stdio::Result<()> {
+        self.file.flush()
+    }
+}
+
+pub fn read_stdin() -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    stdio::stdin().read_to_end(&mut data)?;
+    Ok(data)
+}
+
+pub fn stdout_writer() -> stdio::StdoutLock<'static> {
+    stdio::stdout().lock()
+}
+
+pub fn write_stdout(data: &[u8]) -> anyhow::Result<()> {
+    let mut stdout = stdio::stdout().lock();
+    stdout.write_all(data)?;
+    Ok(stdout.flush()?)
+}
+
+pub fn create_dir_all(path: &Path) -> anyhow::Result<()> {
+    Ok(fs::create_dir_all(path)?)
+}
+
+// Modification time in Unix seconds
+pub fn modified(path: &Path) -> anyhow::Result<u64> {
+    let time = fs::metadata(path)?.modified()?;
+    Ok(time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0))
+}
+
+// Lists a file, or a directory tree, as ("/"-separated name, path on disk).
+// Names start with the last component of `root`; directory names end with "/".
+// Symbolic links are skipped.
+pub fn walk(root: &str) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let root = Path::new(root);
+    // "." or "dir/.." have no name of their own: take the one of the directory they point to
+    let base = fs::canonicalize(root)?
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid input path: {}", root.display()))?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut entries = Vec::new();
+    walk_into(root, base, &mut entries)?;
+    Ok(entries)
+}
+
+fn walk_into(path: &Path, name: String, entries: &mut Vec<(String, PathBuf)>) -> anyhow::Result<()> {
+    let kind = fs::symlink_metadata(path)?.file_type();
+    if kind.is_file() {
+        entries.push((name, path.to_path_buf()));
+    } else if kind.is_dir() {
+        entries.push((format!("{}/", name), path.to_path_buf()));
+
+        let mut children: Vec<_> = fs::read_dir(path)?.collect::<Result<_, _>>()?;
+        children.sort_by_key(|c| c.file_name());
+        for child in children {
+            let child_name = format!("{}/{}", name, child.file_name().to_string_lossy());
+            walk_into(&child.path(), child_name, entries)?;
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------
+// Secure deletion
+//
+// Overwrites the content with random data (SHRED_PASSES passes, each synced to disk),
+// then truncates the file. On SSDs and on copy-on-write or journaling filesystems,
+// old copies of the blocks may survive anyway; full-disk encryption is the real fix.
+// ---------------------------
+pub fn open_for_shred(path: &str) -> anyhow::Result<File> {
+    Ok(OpenOptions::new().write(true).open(path)?)
+}
+
+// Shreds an open file; used when its name has already been replaced (--in-place)
+pub fn shred_file(mut file: &File) -> anyhow::Result<()> {
+    let len = file.metadata()?.len();
+    let mut buf = vec![0u8; SHRED_CHUNK];
+
+    for _ in 0..SHRED_PASSES {
+        file.seek(SeekFrom::Start(0))?;
+        let mut remaining = len;
+        while remaining > 0 {
+            let n = remaining.min(SHRED_CHUNK as u64) as usize;
+            OsRng.fill_bytes(&mut buf[..n]);
+            file.write_all(&buf[..n])?;
+            remaining -= n as u64;
+        }
+        file.sync_all()?;
+    }
+
+    file.set_len(0)?;
+    Ok(file.sync_all()?)
+}
+
+pub fn shred(path: &str) -> anyhow::Result<()> {
+    shred_file(&open_for_shred(path)?)?;
+    Ok(fs::remove_file(path)?)
+}
+
+// ---------------------------
+// File metadata (encrypt --store-metadata / decrypt --restore-metadata)
+//
+// Outside Unix only the name, the mtime and the read-only flag (from the owner write bit)
+// are kept; extended attributes are neither read nor restored.
+// ---------------------------
+pub fn read_metadata(path: &str) -> anyhow::Result<Metadata> {
+    let info = fs::metadata(path)?;
+
+    #[cfg(unix)]
+    let (mode, mtime) = (Some(info.mode() & 0o7777), Some((info.mtime(), info.mtime_nsec() as u32)));
+    #[cfg(not(unix))]
+    let (mode, mtime) = (
+        None,
+        info.modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| (d.as_secs() as i64, d.subsec_nanos())),
+    );
+
+    let name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned());
+    if let Some(name) = name.as_deref().filter(|name| !metadata::is_plain_name(name)) {
+        anyhow::bail!("Cannot store the file name '{}' ('\\' and ':' are not portable)", name);
+    }
+
+    Ok(Metadata {
+        name,
+        mode,
+        mtime,
+        xattrs: read_xattrs(path),
+    })
+}
+
+// Attributes that cannot be read (or have non UTF-8 names) are left out
+#[cfg(unix)]
+fn read_xattrs(path: &str) -> Vec<(String, Vec<u8>)> {
+    let mut xattrs = Vec::new();
+    if let Ok(names) = xattr::list(path) {
+        for name in names {
+            if let (Some(name), Ok(Some(value))) = (name.to_str(), xattr::get(path, &name)) {
+                xattrs.push((name.to_string(), value));
+            }
+        }
+    }
+    xattrs
+}
+
+#[cfg(not(unix))]
+fn read_xattrs(_path: &str) -> Vec<(String, Vec<u8>)> {
+    Vec::new()
+}
+
+// Mode is applied last, so a read-only mode does not get in the way of the rest.
+// Extended attributes that cannot be set (e.g. security.* without privileges) are reported and skipped.
+pub fn apply_metadata(path: &Path, meta: &Metadata) -> anyhow::Result<()> {
+    for (name, value) in &meta.xattrs {
+        #[cfg(unix)]
+        if let Err(e) = xattr::set(path, name, value) {
+            eprintln!("[WARN] Cannot restore extended attribute {}: {}", name, e);
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = value;
+            eprintln!("[WARN] Cannot restore extended attribute {}: not supported on this platform", name);
+        }
+    }
+
+    if let Some((secs, nanos)) = meta.mtime {
+        // The values come from the file: out of range for this platform means a corrupted block
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(secs.unsigned_abs()))
+                .and_then(|t| t.checked_add(Duration::from_nanos(nanos as u64)))
+        }
+        .ok_or_else(|| anyhow::anyhow!("Corrupted metadata block (modification time out of range)"))?;
+        // Windows needs write access to change file times; on Unix owning the file is enough
+        #[cfg(unix)]
+        let file = File::open(path)?;
+        #[cfg(not(unix))]
+        let file = OpenOptions::new().write(true).open(path)?;
+        file.set_modified(time)?;
+    }
+
+    if let Some(mode) = meta.mode {
+        #[cfg(unix)]
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+        #[cfg(not(unix))]
+        {
+            let mut permissions = fs::metadata(path)?.permissions();
+            permissions.set_readonly(mode & 0o200 == 0);
+            fs::set_permissions(path, permissions)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extreme_mtime_does_not_panic() {
+        let file = NamedTempFile::new().unwrap();
+        // Whether these fit depends on the platform's SystemTime (they do on Linux)
+        for secs in [i64::MIN, i64::MAX] {
+            let meta = Metadata { mtime: Some((secs, 999_999_999)), ..Default::default() };
+            if let Err(e) = apply_metadata(file.path(), &meta) {
+                assert!(e.to_string().contains("out of range") || e.downcast_ref::<stdio::Error>().is_some(), "{}", e);
+            }
+        }
+
+        let meta = Metadata { mtime: Some((-86_400, 500)), ..Default::default() };
+        apply_metadata(file.path(), &meta).unwrap();
+        let modified = fs::metadata(file.path()).unwrap().modified().unwrap();
+        assert_eq!(UNIX_EPOCH.duration_since(modified).unwrap(), Duration::new(86_399, 999_999_500));
+    }
+}
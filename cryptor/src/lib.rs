@@ -0,0 +1,4 @@
+// Library target: shared by the binary (main.rs) and the integration tests in tests/
+pub mod cli;
+pub mod io;
+pub mod crypto;
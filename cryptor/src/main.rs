@@ -1,6 +1,4 @@
-mod cli;
-mod io;
-mod crypto;
+use cryptor::cli;
 
 fn main() -> anyhow::Result<()> {
     let args = cli::parse_args();   // Clap robust
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cryptor::crypto::vault;
+
+// ---------------------------
+// Vault files in tests/data/ansible-vault, password "correct-horse", both from plain.yml:
+//   vault-1.1.yml   $ANSIBLE_VAULT;1.1;AES256
+//   vault-1.2.yml   $ANSIBLE_VAULT;1.2;AES256;prod
+// generate.py rebuilds them following ansible-vault's VaultAES256 code.
+// ---------------------------
+const PASSWORD: &str = "correct-horse";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/ansible-vault").join(name)
+}
+
+#[test]
+fn decrypts_vault_files() {
+    let plain = fs::read(fixture("plain.yml")).unwrap();
+
+    for (name, id) in [("vault-1.1.yml", None), ("vault-1.2.yml", Some("prod"))] {
+        let data = fs::read(fixture(name)).unwrap();
+        assert_eq!(vault::vault_id(&data).as_deref(), id, "{}", name);
+        let out = vault::decrypt(PASSWORD, &data).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        assert_eq!(out, plain, "{}", name);
+    }
+}
+
+#[test]
+fn decrypts_indented_inline_value() {
+    // `ansible-vault encrypt_string` output, as pasted under a `!vault |` key
+    let data = fs::read_to_string(fixture("vault-1.1.yml")).unwrap();
+    let indented: String = data.lines().map(|line| format!("          {}\n", line)).collect();
+    let plain = fs::read(fixture("plain.yml")).unwrap();
+    assert_eq!(vault::decrypt(PASSWORD, indented.as_bytes()).unwrap(), plain);
+}
+
+#[test]
+fn rejects_wrong_password_and_tampering() {
+    let data = fs::read(fixture("vault-1.2.yml")).unwrap();
+    assert!(vault::decrypt("wrong", &data).is_err());
+
+    // Flip one hex digit of the payload
+    let mut tampered = data.clone();
+    let pos = tampered.len() - 2;
+    tampered[pos] = if tampered[pos] == b'0' { b'1' } else { b'0' };
+    assert!(vault::decrypt(PASSWORD, &tampered).is_err());
+}
+
+#[test]
+fn rekey_keeps_the_vault_id() {
+    let data = fs::read(fixture("vault-1.2.yml")).unwrap();
+    let rekeyed = vault::rekey(PASSWORD, "battery-staple", &data).unwrap();
+    assert!(rekeyed.starts_with(b"$ANSIBLE_VAULT;1.2;AES256;prod\n"));
+    assert_eq!(vault::decrypt("battery-staple", &rekeyed).unwrap(), fs::read(fixture("plain.yml")).unwrap());
+}
@@ -0,0 +1,33 @@
+# Rebuilds the fixtures the way ansible-vault's VaultAES256 does (ansible/parsing/vault/__init__.py):
+#   python3 generate.py   (needs the `cryptography` package)
+import os
+from binascii import hexlify
+
+from cryptography.hazmat.primitives import hashes, hmac, padding
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+from cryptography.hazmat.primitives.kdf.pbkdf2 import PBKDF2HMAC
+
+
+def encrypt(plaintext, password):
+    salt = os.urandom(32)
+    key = PBKDF2HMAC(hashes.SHA256(), 2 * 32 + 16, salt, 10000).derive(password)
+    key1, key2, iv = key[:32], key[32:64], key[64:]
+
+    padder = padding.PKCS7(128).padder()
+    encryptor = Cipher(algorithms.AES(key1), modes.CTR(iv)).encryptor()
+    ciphertext = encryptor.update(padder.update(plaintext) + padder.finalize()) + encryptor.finalize()
+
+    mac = hmac.HMAC(key2, hashes.SHA256())
+    mac.update(ciphertext)
+    return hexlify(b"\n".join([hexlify(salt), hexlify(mac.finalize()), hexlify(ciphertext)]))
+
+
+def envelope(payload, vault_id=None):
+    header = b"$ANSIBLE_VAULT;1.1;AES256" if vault_id is None else b"$ANSIBLE_VAULT;1.2;AES256;" + vault_id
+    lines = [header] + [payload[i:i + 80] for i in range(0, len(payload), 80)]
+    return b"\n".join(lines) + b"\n"
+
+
+plaintext = open("plain.yml", "rb").read()
+open("vault-1.1.yml", "wb").write(envelope(encrypt(plaintext, b"correct-horse")))
+open("vault-1.2.yml", "wb").write(envelope(encrypt(plaintext, b"correct-horse"), b"prod"))
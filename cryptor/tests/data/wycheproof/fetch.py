@@ -0,0 +1,24 @@
+# Replaces the JSON files here with the upstream Project Wycheproof ones, unmodified, and records
+# the commit they come from in SOURCE:   python3 fetch.py [commit]   (default: the tip of main)
+import json
+import sys
+import urllib.request
+
+REPO = "C2SP/wycheproof"
+FILES = ["aes_gcm_test.json", "chacha20_poly1305_test.json"]
+
+
+def get(url):
+    with urllib.request.urlopen(url) as response:
+        return response.read()
+
+
+commit = sys.argv[1] if len(sys.argv) > 1 else json.loads(get(f"https://api.github.com/repos/{REPO}/commits/main"))["sha"]
+
+for name in FILES:
+    data = get(f"https://raw.githubusercontent.com/{REPO}/{commit}/testvectors_v1/{name}")
+    json.loads(data)  # fail before overwriting anything with an error page
+    open(name, "wb").write(data)
+
+with open("SOURCE", "w") as source:
+    source.write(f"https://github.com/{REPO} {commit} testvectors_v1/\n")
@@ -0,0 +1,83 @@
+# Writes aes.zip from the WinZip AE-x specification (https://www.winzip.com/en/support/aes-encryption/),
+# independently of cryptor:   python3 generate.py   (needs the `cryptography` package)
+#
+# Entries (password "correct-horse"):
+#   docs/                 directory, unencrypted
+#   docs/readme.txt       AE-2, AES-256, deflated  (CRC field 0, as 7-Zip and WinZip write small files)
+#   docs/notes.txt        AE-1, AES-128, stored    (CRC field set)
+#   short.txt             AE-2, AES-192, stored
+import hashlib
+import hmac
+import os
+import struct
+import zlib
+
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+
+PASSWORD = b"correct-horse"
+DOS_TIME, DOS_DATE = 0x6000, 0x5A53  # 2025-02-19 12:00:00
+
+
+def aes_ctr_le(key, data):
+    # WinZip counter mode: 128-bit little-endian counter starting at 1
+    ecb = Cipher(algorithms.AES(key), modes.ECB()).encryptor()
+    out = bytearray()
+    for i in range(0, len(data), 16):
+        block = ecb.update((i // 16 + 1).to_bytes(16, "little"))
+        out += bytes(a ^ b for a, b in zip(data[i:i + 16], block))
+    return bytes(out)
+
+
+def encrypt(data, strength):
+    key_len = {1: 16, 2: 24, 3: 32}[strength]
+    salt = os.urandom(key_len // 2)
+    material = hashlib.pbkdf2_hmac("sha1", PASSWORD, salt, 1000, 2 * key_len + 2)
+    enc_key, mac_key, pwv = material[:key_len], material[key_len:2 * key_len], material[2 * key_len:]
+    ciphertext = aes_ctr_le(enc_key, data)
+    auth = hmac.new(mac_key, ciphertext, hashlib.sha1).digest()[:10]
+    return salt + pwv + ciphertext + auth
+
+
+def entry(name, data, version=None, strength=3, deflate=False):
+    name = name.encode()
+    if version is None:
+        return dict(name=name, flags=0, method=0, crc=0, body=b"", size=0, extra=b"")
+    payload = data
+    if deflate:
+        c = zlib.compressobj(9, zlib.DEFLATED, -15)
+        payload = c.compress(data) + c.flush()
+    crc = zlib.crc32(data) if version == 1 else 0
+    extra = struct.pack("<HHH2sBH", 0x9901, 7, version, b"AE", strength, 8 if deflate else 0)
+    return dict(name=name, flags=1, method=99, crc=crc, body=encrypt(payload, strength), size=len(data), extra=extra)
+
+
+entries = [
+    entry("docs/", b""),
+    entry("docs/readme.txt", b"Attack at dawn. " * 20, version=2, strength=3, deflate=True),
+    entry("docs/notes.txt", b"The quick brown fox jumps over the lazy dog.\n", version=1, strength=1),
+    entry("short.txt", b"hi", version=2, strength=2),
+]
+
+out, central = bytearray(), bytearray()
+for e in entries:
+    offset = len(out)
+    fixed = (51, e["flags"], e["method"], DOS_TIME, DOS_DATE, e["crc"], len(e["body"]), e["size"])
+    out += struct.pack("<IHHHHHIIIHH", 0x04034B50, *fixed, len(e["name"]), len(e["extra"]))
+    out += e["name"] + e["extra"] + e["body"]
+    attrs = (0o040755 << 16 | 0x10) if e["name"].endswith(b"/") else 0o100644 << 16
+    central += struct.pack("<IHHHHHHIIIHHHHHII", 0x02014B50, 0x033F, *fixed,
+                           len(e["name"]), len(e["extra"]), 0, 0, 0, attrs, offset)
+    central += e["name"] + e["extra"]
+
+start = len(out)
+out += central
+out += struct.pack("<IHHHHIIH", 0x06054B50, 0, 0, len(entries), len(entries), len(central), start, 0)
+open("aes.zip", "wb").write(out)
+
+# injected.zip: aes.zip with an unencrypted entry appended by someone without the password
+import shutil
+import zipfile
+
+shutil.copy("aes.zip", "injected.zip")
+with zipfile.ZipFile("injected.zip", "a") as archive:
+    archive.writestr("evil.txt", b"not from the author\n")
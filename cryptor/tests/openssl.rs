@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cryptor::crypto::openssl;
+
+// ---------------------------
+// Files produced by OpenSSL 3.5 (tests/data/openssl), password "correct-horse":
+//   openssl enc -aes-256-{cbc,ctr} -pbkdf2 -iter 10000 -md sha256   -> {cbc,ctr}-pbkdf2.bin
+//   openssl enc -aes-256-{cbc,ctr} -md sha256                       -> {cbc,ctr}-sha256.bin
+//   openssl enc -aes-256-{cbc,ctr} -md md5                          -> {cbc,ctr}-md5.bin
+// all from plain.txt
+// ---------------------------
+const PASSWORD: &str = "correct-horse";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/openssl").join(name)
+}
+
+#[test]
+fn decrypts_openssl_enc_files() {
+    let plain = fs::read(fixture("plain.txt")).unwrap();
+
+    for mode in ["cbc", "ctr"] {
+        for kdf in openssl::KDFS {
+            let data = fs::read(fixture(&format!("{}-{}.bin", mode, kdf))).unwrap();
+            let algo = format!("aes-256-{}", mode);
+            let out = openssl::decrypt(&algo, kdf, PASSWORD, &data)
+                .unwrap_or_else(|e| panic!("{} / {}: {}", algo, kdf, e));
+            assert_eq!(out, plain, "{} / {}", algo, kdf);
+        }
+    }
+}
+
+#[test]
+fn rejects_wrong_password_or_kdf() {
+    let plain = fs::read(fixture("plain.txt")).unwrap();
+    let data = fs::read(fixture("cbc-pbkdf2.bin")).unwrap();
+
+    // CBC usually catches a wrong key through the padding, CTR never can
+    for (kdf, password) in [("pbkdf2", "wrong"), ("md5", PASSWORD)] {
+        let out = openssl::decrypt("aes-256-cbc", kdf, password, &data);
+        assert!(out.map_or(true, |out| out != plain));
+    }
+    assert!(openssl::decrypt("aes-256-cbc", "pbkdf2", PASSWORD, &data[..12]).is_err());
+}
+
+#[test]
+fn roundtrip_matches_openssl_layout() {
+    let data = openssl::encrypt("aes-256-ctr", "sha256", PASSWORD, b"hello").unwrap();
+    assert!(data.starts_with(b"Salted__"));
+    assert_eq!(data.len(), 8 + 8 + 5);
+    assert_eq!(openssl::decrypt("aes-256-ctr", "sha256", PASSWORD, &data).unwrap(), b"hello");
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cryptor::crypto::sodium;
+
+// ---------------------------
+// Files written by libsodium 1.0.18 itself (tests/data/libsodium), key 00 01 .. 1f,
+// plaintext byte i = (7 * i + 3) mod 256 for i < 10000:
+//   secretbox.bin     nonce 64 65 .. 7b || crypto_secretbox_easy
+//   secretstream.bin  crypto_secretstream_xchacha20poly1305 header || push of 4096-byte chunks, last one FINAL
+// ---------------------------
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/libsodium").join(name)
+}
+
+fn key() -> [u8; sodium::KEY_LEN] {
+    std::array::from_fn(|i| i as u8)
+}
+
+fn plaintext() -> Vec<u8> {
+    (0..10_000u32).map(|i| ((i * 7 + 3) % 256) as u8).collect()
+}
+
+#[test]
+fn decrypts_libsodium_output() {
+    let secretbox = fs::read(fixture("secretbox.bin")).unwrap();
+    assert_eq!(sodium::secretbox_decrypt(&key(), &secretbox).unwrap(), plaintext());
+
+    let secretstream = fs::read(fixture("secretstream.bin")).unwrap();
+    assert_eq!(sodium::secretstream_decrypt(&key(), &secretstream).unwrap(), plaintext());
+}
+
+#[test]
+fn libsodium_stream_layout() {
+    // The --help text and the fixture both assume 4096-byte chunks
+    assert_eq!(sodium::CHUNK_LEN, 4096);
+
+    // header (24) || 3 chunks of 17 bytes of overhead each
+    let ours = sodium::secretstream_encrypt(&key(), &plaintext()).unwrap();
+    let theirs = fs::read(fixture("secretstream.bin")).unwrap();
+    assert_eq!(ours.len(), theirs.len());
+}
+
+#[test]
+fn rejects_tampered_libsodium_output() {
+    let mut secretstream = fs::read(fixture("secretstream.bin")).unwrap();
+    secretstream[24 + 4096 + 17 + 10] ^= 1;
+    assert!(sodium::secretstream_decrypt(&key(), &secretstream).is_err());
+
+    let secretstream = fs::read(fixture("secretstream.bin")).unwrap();
+    assert!(sodium::secretstream_decrypt(&key(), &secretstream[..24 + 2 * (4096 + 17)]).is_err());
+
+    let mut secretbox = fs::read(fixture("secretbox.bin")).unwrap();
+    let last = secretbox.len() - 1;
+    secretbox[last] ^= 1;
+    assert!(sodium::secretbox_decrypt(&key(), &secretbox).is_err());
+}
+
+#[test]
+fn roundtrip_on_chunk_boundaries() {
+    for len in [0, 1, 4095, 4096, 4097, 2 * 4096, 10_000] {
+        let data = &plaintext()[..len];
+        let stream = sodium::secretstream_encrypt(&key(), data).unwrap();
+        assert_eq!(sodium::secretstream_decrypt(&key(), &stream).unwrap(), data, "secretstream {}", len);
+
+        let sealed = sodium::secretbox_encrypt(&key(), data).unwrap();
+        assert_eq!(sodium::secretbox_decrypt(&key(), &sealed).unwrap(), data, "secretbox {}", len);
+    }
+}
+
+#[test]
+fn verify_locates_the_damage() {
+    use cryptor::crypto::verify::Verdict;
+
+    let stream = fs::read(fixture("secretstream.bin")).unwrap();
+    let len = stream.len();
+    let chunk = 4096 + 17;
+    assert_eq!(sodium::secretstream_verify(&key(), &stream), Verdict::Valid { chunks: 3, bytes: len });
+
+    // Cut after the second chunk (no final one), and inside the header
+    assert_eq!(sodium::secretstream_verify(&key(), &stream[..24 + 2 * chunk]), Verdict::Truncated { offset: 24 + 2 * chunk });
+    assert_eq!(sodium::secretstream_verify(&key(), &stream[..10]), Verdict::Truncated { offset: 10 });
+
+    let mut tampered = stream.clone();
+    tampered[24 + chunk + 10] ^= 1;
+    assert_eq!(sodium::secretstream_verify(&key(), &tampered), Verdict::Tampered { start: 24 + chunk, end: 24 + 2 * chunk });
+
+    // Appended bytes land in the short final chunk, which then fails
+    let mut trailing = stream.clone();
+    trailing.extend_from_slice(b"junk");
+    assert_eq!(sodium::secretstream_verify(&key(), &trailing), Verdict::Tampered { start: 24 + 2 * chunk, end: len + 4 });
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use cryptor::crypto::{aes, chacha};
+use serde_json::Value;
+
+// ---------------------------
+// Project Wycheproof AEAD vectors (tests/data/wycheproof), run through the AEAD step of
+// aes::decrypt / chacha::decrypt (aes::open / chacha::open), i.e. after key derivation.
+//
+// tests/data/wycheproof/fetch.py replaces the JSON files with the upstream ones, unmodified, and
+// records their commit in SOURCE; until then they are the 256/96/128 subset shipped with RustCrypto.
+//
+// cryptor only uses 256-bit keys, 96-bit nonces and 128-bit tags: groups with other key
+// sizes are skipped, and every case of a group with other nonce sizes or shorter tags,
+// valid or not upstream, must be rejected.
+// ---------------------------
+type Aead = fn(&[u8; 32], &[u8], &[u8], &[u8]) -> Result<Vec<u8>>;
+
+fn unhex(test: &Value, field: &str) -> Vec<u8> {
+    hex::decode(test[field].as_str().unwrap()).unwrap()
+}
+
+fn run(file: &str, seal: Aead, open: Aead) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/wycheproof").join(file);
+    let doc: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+
+    let mut checked = 0;
+    for group in doc["testGroups"].as_array().unwrap() {
+        if group["keySize"] != 256 {
+            continue;
+        }
+        let supported = group["ivSize"] == 96 && group["tagSize"] == 128;
+
+        for test in group["tests"].as_array().unwrap() {
+            let id = &test["tcId"];
+            let key: [u8; 32] = unhex(test, "key").try_into().unwrap();
+            let (iv, aad, msg) = (unhex(test, "iv"), unhex(test, "aad"), unhex(test, "msg"));
+            let mut sealed = unhex(test, "ct");
+            sealed.extend_from_slice(&unhex(test, "tag"));
+
+            let result = test["result"].as_str().unwrap();
+            let valid = result == "valid" && supported;
+            if group["ivSize"] != 96 {
+                assert!(seal(&key, &iv, &aad, &msg).is_err(), "tcId {}: {}-bit nonce accepted by seal", id, iv.len() * 8);
+            }
+
+            match open(&key, &iv, &aad, &sealed) {
+                Ok(_) if !supported => panic!("tcId {}: unsupported nonce or tag size accepted", id),
+                Ok(plain) => {
+                    assert!(valid || result == "acceptable", "tcId {}: invalid case accepted ({})", id, test["comment"]);
+                    assert_eq!(plain, msg, "tcId {}: wrong plaintext", id);
+                }
+                Err(_) => assert!(!valid, "tcId {}: valid case rejected ({})", id, test["comment"]),
+            }
+
+            if valid {
+                assert_eq!(seal(&key, &iv, &aad, &msg).unwrap(), sealed, "tcId {}: wrong ciphertext", id);
+
+                // Truncated tags, down to no tag at all, are never accepted
+                for cut in 1..=16 {
+                    assert!(
+                        open(&key, &iv, &aad, &sealed[..sealed.len() - cut]).is_err(),
+                        "tcId {}: tag truncated by {} bytes accepted",
+                        id,
+                        cut
+                    );
+                }
+            }
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "{}: no usable test group", file);
+}
+
+#[test]
+fn aes_gcm() {
+    run("aes_gcm_test.json", aes::seal, aes::open);
+}
+
+#[test]
+fn chacha20_poly1305() {
+    run("chacha20_poly1305_test.json", chacha::seal, chacha::open);
+}
+
+#[test]
+fn other_nonce_sizes_are_rejected() {
+    // Wycheproof also has 0, 64, 128 and 1024-bit nonce groups; neither AEAD may pad or cut them
+    let key = [7u8; 32];
+    for (name, seal, open) in [("aes", aes::seal as Aead, aes::open as Aead), ("chacha", chacha::seal, chacha::open)] {
+        let good = seal(&key, &[0; 12], b"", b"message").unwrap();
+        for len in [0, 8, 11, 13, 16, 24, 128] {
+            let nonce = vec![0; len];
+            assert!(seal(&key, &nonce, b"", b"message").is_err(), "{}: {}-byte nonce accepted by seal", name, len);
+            assert!(open(&key, &nonce, b"", &good).is_err(), "{}: {}-byte nonce accepted by open", name, len);
+        }
+    }
+}
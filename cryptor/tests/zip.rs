@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::Path;
+
+use cryptor::crypto::zip;
+use cryptor::io;
+
+// ---------------------------
+// tests/data/zip/aes.zip, password "correct-horse": AE-2 and AE-1 entries (AES-256 / 128 / 192,
+// deflated and stored) and a directory. generate.py writes it from the WinZip specification,
+// then injected.zip: the same archive with an unencrypted entry appended by Python's zipfile.
+// ---------------------------
+const PASSWORD: &str = "correct-horse";
+
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/zip").join(name)).unwrap()
+}
+
+fn archive() -> Vec<u8> {
+    fixture("aes.zip")
+}
+
+#[test]
+fn decrypts_winzip_aes_archive() {
+    let entries = zip::decrypt(PASSWORD, &archive()).unwrap();
+    let files: Vec<(&str, &[u8])> = entries.iter().map(|e| (e.name.as_str(), &e.data[..])).collect();
+    assert_eq!(
+        files,
+        [
+            ("docs/", &b""[..]),
+            ("docs/readme.txt", "Attack at dawn. ".repeat(20).as_bytes()),
+            ("docs/notes.txt", b"The quick brown fox jumps over the lazy dog.\n"),
+            ("short.txt", b"hi"),
+        ]
+    );
+    assert!(entries[0].is_dir());
+}
+
+#[test]
+fn lists_without_the_password() {
+    let listing = zip::list(&archive()).unwrap();
+    let found: Vec<(&str, &str)> = listing.iter().map(|l| (l.name.as_str(), l.encryption.as_str())).collect();
+    assert_eq!(found[1], ("docs/readme.txt", "AES-256 (AE-2)"));
+    assert_eq!(found[2].0, "docs/notes.txt");
+    assert!(found[2].1.contains("AE-1"));
+}
+
+#[test]
+fn rejects_wrong_password_and_tampering() {
+    assert!(zip::decrypt("wrong", &archive()).is_err());
+
+    // Sixth byte of the readme ciphertext: name, extra field (11), salt (16) and password check (2) come first
+    let mut data = archive();
+    let pos = data.windows(15).position(|w| w == b"docs/readme.txt").unwrap() + 15 + 11 + 16 + 2 + 5;
+    data[pos] ^= 1;
+    assert!(zip::decrypt(PASSWORD, &data).is_err());
+}
+
+#[test]
+fn rejects_unencrypted_entries() {
+    let err = zip::decrypt(PASSWORD, &fixture("injected.zip")).err().unwrap();
+    assert!(err.to_string().contains("evil.txt: entry is not encrypted"), "{}", err);
+}
+
+#[test]
+fn ignores_the_declared_size_until_decompressed() {
+    // The central directory is not authenticated: claim a 4 GiB readme
+    let mut data = archive();
+    let central = data.windows(4).rposition(|w| w == [0x50, 0x4b, 0x01, 0x02]).unwrap();
+    let readme = data[..central].windows(4).rposition(|w| w == [0x50, 0x4b, 0x01, 0x02]).unwrap();
+    let readme = data[..readme].windows(4).rposition(|w| w == [0x50, 0x4b, 0x01, 0x02]).unwrap();
+    assert_eq!(&data[readme + 46..readme + 46 + 15], b"docs/readme.txt");
+    data[readme + 24..readme + 28].copy_from_slice(&0xFFFF_FFFEu32.to_le_bytes());
+
+    let err = zip::decrypt(PASSWORD, &data).err().unwrap();
+    assert!(err.to_string().contains("size mismatch"), "{}", err);
+}
+
+#[test]
+fn walk_names_dot_after_the_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), b"fn main() {}").unwrap();
+
+    let dotted = root.join("src").join("..");
+    let entries = io::walk(dotted.to_str().unwrap()).unwrap();
+    let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["project/", "project/src/", "project/src/main.rs"]);
+}